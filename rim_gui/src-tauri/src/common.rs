@@ -7,11 +7,13 @@ use std::{
 
 use super::Result;
 use rim::{
-    components::Component,
-    toolset_manifest::ToolsetManifest,
+    components::{self, Component},
+    toolset_manifest::{License, ToolsetManifest},
+    update::{ManagerTooOld, UpdateOpt},
     utils::{self, Progress},
     InstallConfiguration,
 };
+use tauri::{api::dialog, AppHandle, Manager};
 
 pub(crate) const MESSAGE_UPDATE_EVENT: &str = "update-message";
 pub(crate) const PROGRESS_UPDATE_EVENT: &str = "update-progress";
@@ -72,6 +74,12 @@ pub(crate) fn install_toolkit_in_new_thread(
         // TODO: Use continuous progress
         let config =
             InstallConfiguration::new(&install_dir, &manifest)?.progress_indicator(Some(progress));
+        let licenses = config.licenses_to_accept(&components_list);
+        let accepted_licenses = accept_licenses(&window, &licenses)?;
+        let run_hooks = confirm_hooks(&window, &components::post_install_hooks(&components_list));
+        let config = config
+            .accepted_licenses(accepted_licenses)
+            .run_hooks(run_hooks);
         if is_update {
             config.update(components_list)?;
         } else {
@@ -85,6 +93,87 @@ pub(crate) fn install_toolkit_in_new_thread(
     });
 }
 
+/// Offer a self update in a dialog if `result` failed because this manager is too old
+/// to handle a toolkit, the `result` is returned as is.
+pub(crate) fn self_update_if_too_old<T>(
+    app: &AppHandle,
+    result: anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let Some(too_old) = result.as_ref().err().and_then(ManagerTooOld::find_in) else {
+        return result;
+    };
+
+    let app_ = app.clone();
+    dialog::ask(
+        app.get_focused_window().as_ref(),
+        t!("update_available"),
+        t!(
+            "ask_self_update_for_toolkit",
+            required = too_old.required,
+            current = env!("CARGO_PKG_VERSION")
+        ),
+        move |yes| {
+            if yes {
+                if let Ok(true) = UpdateOpt::new().self_update() {
+                    app_.restart();
+                }
+            }
+        },
+    );
+    result
+}
+
+/// Show the licenses of tools in dialogs and ask the user to accept them one by one,
+/// returns the names of tools whose license was accepted.
+///
+/// Note: this blocks current thread, so do not call it in the main thread.
+pub(crate) fn accept_licenses(
+    window: &tauri::Window,
+    licenses: &[(&str, &License)],
+) -> anyhow::Result<Vec<String>> {
+    let mut accepted = vec![];
+    for (name, license) in licenses {
+        let mut message = t!("license_of", name = name, license = license.name).to_string();
+        if let Some(url) = &license.url {
+            message.push_str(&format!("\n{url}"));
+        }
+        if let Some(path) = &license.path {
+            message.push_str(&format!("\n{}", utils::read_to_string("license", path)?));
+        }
+        message.push_str(&format!(
+            "\n\n{}",
+            t!("question_accept_license", name = name)
+        ));
+
+        if !tauri::api::dialog::blocking::ask(
+            Some(window),
+            t!("license_title", name = name),
+            message,
+        ) {
+            anyhow::bail!(
+                "installation cancelled because the license of '{name}' was not accepted"
+            );
+        }
+        accepted.push(name.to_string());
+    }
+    Ok(accepted)
+}
+
+/// Show the hooks that are about to run in a dialog and ask the user whether to run them,
+/// returns `true` without asking if there are no hooks.
+///
+/// Note: this blocks current thread, so do not call it in the main thread.
+pub(crate) fn confirm_hooks(window: &tauri::Window, hooks: &[String]) -> bool {
+    if hooks.is_empty() {
+        return true;
+    }
+    tauri::api::dialog::blocking::ask(
+        Some(window),
+        t!("run_hooks"),
+        t!("confirm_running_hooks", list = hooks.join("\n")),
+    )
+}
+
 #[derive(serde::Serialize)]
 pub struct Language {
     pub id: String,
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
@@ -7,8 +8,8 @@ use tauri::api::dialog::FileDialogBuilder;
 use super::{common, INSTALL_DIR};
 use crate::error::Result;
 use rim::components::Component;
-use rim::toolset_manifest::{get_toolset_manifest, ToolsetManifest};
-use rim::{try_it, utils};
+use rim::toolset_manifest::{get_toolset_manifest, Preset, ToolsetManifest};
+use rim::{preflight, try_it};
 
 static TOOLSET_MANIFEST: OnceLock<ToolsetManifest> = OnceLock::new();
 
@@ -22,6 +23,7 @@ pub(super) fn main() -> Result<()> {
             select_folder,
             check_install_path,
             get_component_list,
+            get_presets,
             install_toolchain,
             run_app,
             welcome_label,
@@ -91,10 +93,21 @@ fn check_install_path(path: String) -> Option<String> {
     } else if Path::new(&path).is_relative() {
         // We won't accept relative path because the result might gets a little bit unpredictable
         Some(t!("notify_relative_path").to_string())
-    } else if utils::is_root_dir(path) {
-        Some(t!("notify_root_dir").to_string())
     } else {
-        None
+        let path = Path::new(&path);
+        let mut issues = preflight::check_path(path);
+        // Only use the `size` hints here, asking the servers would freeze the page.
+        let tools = cached_manifest().current_target_tools().unwrap_or_default();
+        let required = preflight::estimate_size(&tools, true, None);
+        issues.extend(preflight::check_space(path, required));
+
+        (!issues.is_empty()).then(|| {
+            issues
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
     }
 }
 
@@ -104,6 +117,11 @@ fn get_component_list() -> Result<Vec<Component>> {
     Ok(cached_manifest().current_target_components(true)?)
 }
 
+#[tauri::command]
+fn get_presets() -> BTreeMap<String, Preset> {
+    cached_manifest().presets().clone()
+}
+
 #[tauri::command]
 fn welcome_label() -> String {
     t!("welcome", product = t!("product")).into()
@@ -111,10 +129,10 @@ fn welcome_label() -> String {
 
 // Make sure this function is called first after launch.
 #[tauri::command]
-fn load_manifest_and_ret_version() -> Result<String> {
+fn load_manifest_and_ret_version(app: tauri::AppHandle) -> Result<String> {
     // TODO: Give an option for user to specify another manifest.
     // note that passing command args currently does not work due to `windows_subsystem = "windows"` attr
-    let mut manifest = get_toolset_manifest(None, false)?;
+    let mut manifest = common::self_update_if_too_old(&app, get_toolset_manifest(None, false))?;
     manifest.adjust_paths()?;
 
     let m = TOOLSET_MANIFEST.get_or_init(|| manifest);
@@ -12,6 +12,7 @@ use anyhow::Context;
 use rim::UninstallConfiguration;
 use rim::{
     components::Component,
+    history::{self, HistoryEntry},
     toolkit::{self, Toolkit},
     toolset_manifest::{get_toolset_manifest, ToolsetManifest},
     update::{self, UpdateOpt},
@@ -36,6 +37,7 @@ pub(super) fn main() -> Result<()> {
             get_installed_kit,
             get_available_kits,
             get_install_dir,
+            get_history,
             uninstall_toolkit,
             install_toolkit,
             maybe_self_update,
@@ -81,11 +83,10 @@ fn get_installed_kit(reload: bool) -> Result<Option<Toolkit>> {
 }
 
 #[tauri::command]
-fn get_available_kits(reload: bool) -> Result<Vec<Toolkit>> {
-    Ok(toolkit::installable_toolkits(reload, false)?
-        .into_iter()
-        .cloned()
-        .collect())
+fn get_available_kits(app: AppHandle, reload: bool) -> Result<Vec<Toolkit>> {
+    let toolkits =
+        common::self_update_if_too_old(&app, toolkit::installable_toolkits(reload, false))?;
+    Ok(toolkits.into_iter().cloned().collect())
 }
 
 #[tauri::command]
@@ -93,6 +94,14 @@ fn get_install_dir() -> String {
     rim::get_installed_dir().to_string_lossy().to_string()
 }
 
+/// Get the operations made to this installation, from the newest to the oldest.
+#[tauri::command]
+fn get_history() -> Result<Vec<HistoryEntry>> {
+    let mut entries = history::load(rim::get_installed_dir())?;
+    entries.reverse();
+    Ok(entries)
+}
+
 #[tauri::command(rename_all = "snake_case")]
 fn uninstall_toolkit(window: tauri::Window, remove_self: bool) -> Result<()> {
     let window = Arc::new(window);
@@ -107,7 +116,8 @@ fn uninstall_toolkit(window: tauri::Window, remove_self: bool) -> Result<()> {
         let progress = Progress::new(&pos_cb);
 
         let config = UninstallConfiguration::init(Some(progress))?;
-        config.uninstall(remove_self)?;
+        let run_hooks = common::confirm_hooks(&window, &config.hook_descriptions(None));
+        config.run_hooks(run_hooks).uninstall(remove_self)?;
 
         window.emit(ON_COMPLETE_EVENT, ())?;
         Ok(())
@@ -168,13 +178,13 @@ fn maybe_self_update(app: AppHandle) -> Result<()> {
 /// download its manifest from the server, and we can then return a list of
 /// components that are loaded from it.
 #[tauri::command]
-fn handle_toolkit_install_click(url: String) -> Result<Vec<Component>> {
+fn handle_toolkit_install_click(app: AppHandle, url: String) -> Result<Vec<Component>> {
     // the `url` input was converted from `Url`, so it will definitely be convert back without issue,
     // thus the below line should never panic
     let url_ = utils::force_parse_url(&url);
 
     // load the manifest for components information
-    let manifest = get_toolset_manifest(Some(&url_), false)?;
+    let manifest = common::self_update_if_too_old(&app, get_toolset_manifest(Some(&url_), false))?;
     let components = manifest.current_target_components(false)?;
 
     // cache the selected toolset manifest
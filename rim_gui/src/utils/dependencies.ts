@@ -0,0 +1,54 @@
+import type { CheckGroup, Component } from './index';
+
+/**
+ * Check every (direct or indirect) dependency of the checked components,
+ * so that a tool will never be installed without the tools it depends on.
+ */
+export function checkDependencies(groups: CheckGroup<Component>[]) {
+  const items = groups.flatMap((group) => group.items);
+  const pending = items.filter((item) => item.checked);
+
+  while (pending.length > 0) {
+    const deps = pending.pop()?.value.toolInstaller?.['depends-on'] ?? [];
+    for (const dep of deps) {
+      const depItem = items.find((item) => item.value.name === dep);
+      if (depItem && !depItem.checked) {
+        depItem.checked = true;
+        pending.push(depItem);
+      }
+    }
+  }
+}
+
+/**
+ * Uncheck the components that cannot be installed along with other checked components,
+ * the ones in `preferred` (usually the ones just checked by the user) are kept first.
+ */
+export function uncheckConflicts(
+  groups: CheckGroup<Component>[],
+  preferred: Component[] = []
+) {
+  const preferredIds = new Set(preferred.map((comp) => comp.id));
+  const checked = groups
+    .flatMap((group) => group.items)
+    .filter((item) => item.checked)
+    .sort(
+      (a, b) =>
+        Number(preferredIds.has(b.value.id)) -
+        Number(preferredIds.has(a.value.id))
+    );
+  const kept: Component[] = [];
+
+  for (const item of checked) {
+    const conflicted = kept.some(
+      (comp) =>
+        comp.conflicts?.includes(item.value.name) ||
+        item.value.conflicts?.includes(comp.name)
+    );
+    if (conflicted && !item.disabled) {
+      item.checked = false;
+    } else {
+      kept.push(item.value);
+    }
+  }
+}
@@ -4,4 +4,5 @@ export * from './types/CheckBoxGroup';
 export * from './installConf';
 export * from './invokeCommand';
 export * from './progress';
+export * from './dependencies';
 export * from './managerConf';
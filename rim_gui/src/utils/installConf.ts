@@ -1,17 +1,19 @@
 import { ref, Ref } from 'vue';
-import type { Component } from './types/Component';
+import type { Component, Preset } from './types/Component';
 import { invokeCommand } from './invokeCommand';
 import { CheckGroup, CheckItem } from './types/CheckBoxGroup';
 
 class InstallConf {
   path: Ref<string>;
   checkComponents: Ref<CheckItem<Component>[]>;
+  presets: Ref<Record<string, Preset>>;
   isCustomInstall: boolean;
   version: Ref<string>;
 
   constructor(path: string, components: CheckItem<Component>[]) {
     this.path = ref(path);
     this.checkComponents = ref(components);
+    this.presets = ref({});
     this.isCustomInstall = true;
     this.version = ref('');
   }
@@ -114,9 +116,17 @@ class InstallConf {
     }
   }
 
+  async loadPresets() {
+    const presets = await invokeCommand('get_presets');
+    if (presets && typeof presets === 'object') {
+      this.presets.value = presets as Record<string, Preset>;
+    }
+  }
+
   async loadAll() {
     await this.loadDefaultPath();
     await this.loadComponents();
+    await this.loadPresets();
   }
 }
 
@@ -1,3 +1,8 @@
+export interface Preset {
+  description?: string;
+  components: string[];
+}
+
 export interface Component {
   id: number;
   name: string;
@@ -8,9 +13,12 @@ export interface Component {
   desc: string;
   groupName: string | null;
   isToolchainComponent: boolean;
+  isTarget: boolean;
+  conflicts?: string[];
   toolInstaller?: {
     required: boolean;
     optional: boolean;
     path?: string;
+    'depends-on'?: string[];
   };
 }
@@ -10,11 +10,14 @@ use std::{
     io::{self, BufRead, Write},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use indexmap::IndexMap;
-use log::warn;
+use log::{info, warn};
 
-use crate::components::Component;
+use crate::components::{conflicting_pairs, dependencies_of, Component};
+use crate::core::update::{ManagerTooOld, UpdateOpt};
+use crate::toolset_manifest::{License, Preset};
+use crate::utils;
 
 /// A "convenient" helper macro to [`question_single_choice`].
 ///
@@ -78,6 +81,57 @@ pub(crate) type VersionDiffMap<'c> = HashMap<&'c str, (Option<&'c str>, Option<&
 /// Notice that this is an [`IndexMap`], which means the order will be preserved.
 pub(crate) type ComponentChoices<'c> = IndexMap<usize, &'c Component>;
 
+/// Add the dependencies of the chosen components into the choices,
+/// unless a dependency is already installed.
+pub(crate) fn with_dependencies<'c>(
+    all: &'c [Component],
+    mut choices: ComponentChoices<'c>,
+) -> ComponentChoices<'c> {
+    let deps = dependencies_of(all, choices.values().copied());
+    for (idx, comp) in all.iter().enumerate() {
+        if deps.contains(comp.name.as_str()) && !comp.installed {
+            choices.entry(idx).or_insert(comp);
+        }
+    }
+    choices
+}
+
+/// Choose the required components and the ones listed in a `preset`,
+/// unless they are already installed.
+pub(crate) fn preset_choices<'c>(all: &'c [Component], preset: &Preset) -> ComponentChoices<'c> {
+    all.iter()
+        .enumerate()
+        .filter(|(_, c)| !c.installed && (c.required || preset.contains(&c.name)))
+        .collect()
+}
+
+/// Remove the components that cannot be installed along with the ones chosen before them.
+pub(crate) fn without_conflicts(choices: ComponentChoices<'_>) -> ComponentChoices<'_> {
+    let mut kept = ComponentChoices::new();
+    for (idx, comp) in choices {
+        if conflicting_pairs(kept.values().copied().chain([comp])).is_empty() {
+            kept.insert(idx, comp);
+        }
+    }
+    kept
+}
+
+/// Check if there are components that cannot be installed together in the choices,
+/// and warn the user about them if there are.
+pub(crate) fn has_conflicts(choices: &ComponentChoices<'_>) -> bool {
+    let pairs = conflicting_pairs(choices.values().copied());
+    if pairs.is_empty() {
+        return false;
+    }
+    let list = pairs
+        .iter()
+        .map(|(a, b)| format!("'{a}' <-> '{b}'"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    warn!("{}", t!("conflicting_choices", list = list));
+    true
+}
+
 pub(crate) fn question_str<Q: Display, A: Display>(
     question: Q,
     extra: Option<&str>,
@@ -282,6 +336,94 @@ pub(crate) fn confirm<Q: Display>(question: Q, default: bool) -> Result<bool> {
     Ok(choice)
 }
 
+/// Offer the user a self update if `result` failed because this manager is too old
+/// to handle a toolkit, otherwise the `result` is returned as is.
+pub(crate) fn self_update_if_too_old(result: Result<()>) -> Result<()> {
+    let Err(err) = result else {
+        return Ok(());
+    };
+    let Some(too_old) = ManagerTooOld::find_in(&err) else {
+        return Err(err);
+    };
+    let question = t!(
+        "ask_self_update_for_toolkit",
+        required = too_old.required,
+        current = env!("CARGO_PKG_VERSION")
+    );
+    if !confirm(question, true)? {
+        return Err(err);
+    }
+    if UpdateOpt::new().self_update()? {
+        info!("{}", t!("rerun_after_self_update"));
+    }
+    Ok(())
+}
+
+/// Show the hooks that are about to run and ask the user whether to run them,
+/// returns `true` without asking if there are no hooks.
+pub(crate) fn confirm_hooks(hooks: &[String]) -> Result<bool> {
+    if hooks.is_empty() {
+        return Ok(true);
+    }
+    confirm(t!("confirm_running_hooks", list = hooks.join("\n")), false)
+}
+
+/// Show the licenses of the tools that are about to be installed and ask the user to accept them,
+/// returns the names of tools whose license was accepted.
+///
+/// The tools in `accepted` (passed with `--accept-license`) are accepted without asking,
+/// others could not be accepted when running unattended (with `--yes`).
+pub(crate) fn accept_licenses(
+    licenses: &[(&str, &License)],
+    accepted: &[String],
+) -> Result<Vec<String>> {
+    if GlobalOpts::get().yes_to_all {
+        ensure_licenses_accepted(licenses, accepted)?;
+    }
+    let mut result = accepted.to_vec();
+    for (name, license) in licenses {
+        if accepted.iter().any(|n| n == name) {
+            continue;
+        }
+
+        let mut stdout = io::stdout();
+        writeln!(
+            &mut stdout,
+            "\n{}",
+            t!("license_of", name = name, license = license.name)
+        )?;
+        if let Some(url) = &license.url {
+            writeln!(&mut stdout, "{url}")?;
+        }
+        if let Some(path) = &license.path {
+            writeln!(&mut stdout, "{}", utils::read_to_string("license", path)?)?;
+        }
+        if !confirm(t!("question_accept_license", name = name), false)? {
+            bail!("installation cancelled because the license of '{name}' was not accepted");
+        }
+        result.push(name.to_string());
+    }
+    Ok(result)
+}
+
+/// Make sure every license was accepted with `--accept-license` (which are the tools in `accepted`),
+/// since nobody could be asked to accept them when running without interaction.
+pub(crate) fn ensure_licenses_accepted(
+    licenses: &[(&str, &License)],
+    accepted: &[String],
+) -> Result<()> {
+    for (name, license) in licenses {
+        if !accepted.iter().any(|n| n == name) {
+            bail!(
+                "the license '{}' of '{name}' must be accepted with `--accept-license {name}` \
+                when running without interaction",
+                license.name
+            );
+        }
+    }
+    Ok(())
+}
+
 pub(crate) enum Confirm {
     Yes,
     No,
@@ -414,11 +556,26 @@ impl<'c, I: IntoIterator<Item = &'c Component>> ComponentListBuilder<'c, I> {
             .into_iter()
             .map(|c| {
                 let deco = self.decoration.for_component(c);
-                let desc = if self.show_desc {
+                let mut desc = if self.show_desc {
                     format!("\n\t{}: {}", t!("description"), &c.desc)
                 } else {
                     String::new()
                 };
+                let deps = c
+                    .tool_installer
+                    .as_ref()
+                    .map(|info| info.dependencies())
+                    .unwrap_or_default();
+                if self.show_desc && !deps.is_empty() {
+                    desc.push_str(&format!("\n\t{}: {}", t!("depends_on"), deps.join(", ")));
+                }
+                if self.show_desc && !c.conflicts.is_empty() {
+                    desc.push_str(&format!(
+                        "\n\t{}: {}",
+                        t!("conflicts_with"),
+                        c.conflicts.join(", ")
+                    ));
+                }
                 format!("{}{deco}{desc}", &c.name)
             })
             .collect()
@@ -1,6 +1,14 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Subcommand;
+use log::info;
 
+use crate::components::{self, post_install_hooks, Component};
+use crate::core::install::InstallConfiguration;
+use crate::core::uninstall::UninstallConfiguration;
+use crate::fingerprint::InstallationRecord;
+use crate::toolset_manifest::ToolsetManifest;
+
+use super::common::{self, with_dependencies, ComponentChoices};
 use super::ManagerSubcommands;
 
 #[derive(Subcommand, Debug)]
@@ -11,8 +19,15 @@ pub(super) enum ComponentCommand {
         /// Allow insecure connections when download packages from server.
         #[arg(short = 'k', long)]
         insecure: bool,
+        /// Install the components of a preset declared in the manifest as well
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+        /// Accept the license of a tool without asking,
+        /// which is required for tools with licenses when `--yes` is passed.
+        #[arg(long, value_name = "TOOL")]
+        accept_license: Vec<String>,
         /// The list of components to install
-        #[arg(value_name = "COMPONENTS")]
+        #[arg(value_name = "COMPONENTS", required_unless_present = "preset")]
         components: Vec<String>,
     },
     /// Uninstall a set of components, check `list component --installed` for available options
@@ -27,12 +42,64 @@ pub(super) enum ComponentCommand {
 impl ComponentCommand {
     fn execute(&self) -> Result<()> {
         match self {
-            Self::Install { components, .. } => todo!("install components: {components:?}"),
-            Self::Uninstall { components } => todo!("uninstall components: {components:?}"),
+            Self::Install {
+                insecure,
+                preset,
+                accept_license,
+                components,
+            } => install_components(components, preset.as_deref(), *insecure, accept_license),
+            Self::Uninstall { components } => {
+                let config = UninstallConfiguration::init(None)?;
+                let run_hooks = common::confirm_hooks(&config.hook_descriptions(Some(components)))?;
+                config.run_hooks(run_hooks).uninstall_tools(components)
+            }
         }
     }
 }
 
+/// Install the components of the installed toolkit by their `names`,
+/// along with the ones in a `preset` if there is one.
+fn install_components(
+    names: &[String],
+    preset: Option<&str>,
+    insecure: bool,
+    accept_license: &[String],
+) -> Result<()> {
+    let record = InstallationRecord::load_from_install_dir()?;
+    let manifest = ToolsetManifest::load_from_install_dir()?;
+    let all = components::all_components_from_installation(&record)?;
+
+    if let Some(unknown) = names.iter().find(|n| !all.iter().any(|c| &c.name == *n)) {
+        bail!("unknown component '{unknown}', check `list component` for available options");
+    }
+    let mut choices = match preset {
+        Some(name) => common::preset_choices(&all, manifest.preset(name)?),
+        None => ComponentChoices::new(),
+    };
+    for (idx, comp) in all.iter().enumerate() {
+        if !comp.installed && names.contains(&comp.name) {
+            choices.entry(idx).or_insert(comp);
+        }
+    }
+    let choices = with_dependencies(&all, choices);
+    if choices.is_empty() {
+        info!("{}", t!("components_already_installed"));
+        return Ok(());
+    }
+
+    let components: Vec<Component> = choices.into_values().cloned().collect();
+    // check this before asking for licenses and hooks
+    components::check_conflicts_with_installed(&components, record.installed_tools())?;
+    let config = InstallConfiguration::new(&record.root, &manifest)?.insecure(insecure);
+    let licenses = config.licenses_to_accept(&components);
+    let accepted_licenses = common::accept_licenses(&licenses, accept_license)?;
+    let run_hooks = common::confirm_hooks(&post_install_hooks(&components))?;
+    config
+        .accepted_licenses(accepted_licenses)
+        .run_hooks(run_hooks)
+        .install_components(components)
+}
+
 pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<bool> {
     let ManagerSubcommands::Component { command } = cmd else {
         return Ok(false);
@@ -0,0 +1,28 @@
+//! Separated module to handle `history` command in command line.
+
+use crate::get_installed_dir;
+use crate::history;
+
+use super::ManagerSubcommands;
+
+use anyhow::Result;
+use log::info;
+
+/// Execute `history` command.
+pub(super) fn execute(subcommand: &ManagerSubcommands) -> Result<bool> {
+    let ManagerSubcommands::History { limit } = subcommand else {
+        return Ok(false);
+    };
+
+    let entries = history::load(get_installed_dir())?;
+    if entries.is_empty() {
+        info!("{}", t!("no_history"));
+        return Ok(true);
+    }
+    let skip = limit.map_or(0, |n| entries.len().saturating_sub(n));
+    for entry in &entries[skip..] {
+        println!("{entry}");
+    }
+
+    Ok(true)
+}
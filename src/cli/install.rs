@@ -6,21 +6,24 @@ use std::path::{Path, PathBuf};
 
 use crate::cli::common::{self, Confirm};
 use crate::cli::GlobalOpts;
-use crate::components::Component;
+use crate::components::{post_install_hooks, Component};
 use crate::core::install::{
     default_rustup_dist_server, default_rustup_update_root, InstallConfiguration,
     DEFAULT_CARGO_REGISTRY,
 };
-use crate::core::try_it;
-use crate::toolset_manifest::get_toolset_manifest;
+use crate::core::parser::TomlParser;
+use crate::core::{preflight, try_it};
+use crate::lockfile::Lockfile;
+use crate::toolset_manifest::{get_toolset_manifest, Preset};
 use crate::{default_install_dir, utils};
 
 use super::common::{
-    question_single_choice, ComponentChoices, ComponentDecoration, ComponentListBuilder,
+    question_single_choice, with_dependencies, without_conflicts, ComponentChoices,
+    ComponentDecoration, ComponentListBuilder,
 };
 use super::{Installer, ManagerSubcommands};
 
-use anyhow::{bail, Result};
+use anyhow::Result;
 use log::warn;
 
 /// Perform installer actions.
@@ -35,24 +38,48 @@ pub(super) fn execute_installer(installer: &Installer) -> Result<()> {
         rustup_update_root,
         manifest: manifest_src,
         insecure,
+        accept_license,
+        preset,
+        lock,
         ..
     } = installer;
 
-    if matches!(&prefix, Some(p) if utils::is_root_dir(p)) {
-        bail!(t!("notify_root_dir"));
-    }
+    let abs_prefix = if let Some(path) = prefix {
+        let abs_path = utils::to_nomalized_abspath(path, None)?;
+        preflight::ensure_passed(&preflight::check_path(&abs_path))?;
+        abs_path
+    } else {
+        default_install_dir()
+    };
 
     let manifest_url = manifest_src.as_ref().map(|s| s.to_url()).transpose()?;
     let mut manifest = get_toolset_manifest(manifest_url.as_ref(), *insecure)?;
     manifest.adjust_paths()?;
 
-    let component_list = manifest.current_target_components(true)?;
-    let abs_prefix = if let Some(path) = prefix {
-        utils::to_nomalized_abspath(path, None)?
+    let lockfile = lock.as_deref().map(Lockfile::load).transpose()?;
+    let user_opt = if let Some(lockfile) = &lockfile {
+        if lockfile.version != manifest.version {
+            warn!(
+                "{}",
+                t!(
+                    "lockfile_toolkit_mismatch",
+                    locked = lockfile.version.as_deref().unwrap_or("?"),
+                    current = manifest.version.as_deref().unwrap_or("?")
+                )
+            );
+        }
+        CustomInstallOpt {
+            prefix: abs_prefix,
+            components: lockfile.components_from(&manifest)?,
+        }
     } else {
-        default_install_dir()
+        let component_list = manifest.current_target_components(true)?;
+        let preset = preset
+            .as_deref()
+            .map(|name| manifest.preset(name).map(|p| (name, p)))
+            .transpose()?;
+        CustomInstallOpt::collect_from_user(&abs_prefix, component_list, preset)?
     };
-    let user_opt = CustomInstallOpt::collect_from_user(&abs_prefix, component_list)?;
 
     let (registry_name, registry_value) = registry_url
         .as_deref()
@@ -60,7 +87,7 @@ pub(super) fn execute_installer(installer: &Installer) -> Result<()> {
         .unwrap_or(DEFAULT_CARGO_REGISTRY);
     let install_dir = user_opt.prefix;
 
-    InstallConfiguration::new(&install_dir, &manifest)?
+    let config = InstallConfiguration::new(&install_dir, &manifest)?
         .cargo_registry(registry_name, registry_value)
         .rustup_dist_server(
             rustup_dist_server
@@ -72,10 +99,25 @@ pub(super) fn execute_installer(installer: &Installer) -> Result<()> {
                 .clone()
                 .unwrap_or_else(|| default_rustup_update_root().clone()),
         )
-        .insecure(*insecure)
+        .insecure(*insecure);
+    let g_opts = GlobalOpts::get();
+    let licenses = config.licenses_to_accept(&user_opt.components);
+    let (accepted_licenses, run_hooks) = if lockfile.is_some() {
+        // installing from a lockfile is not interactive, so licenses must be accepted
+        // with `--accept-license`, and hooks only run with `--yes`
+        common::ensure_licenses_accepted(&licenses, accept_license)?;
+        (accept_license.clone(), g_opts.yes_to_all)
+    } else {
+        (
+            common::accept_licenses(&licenses, accept_license)?,
+            common::confirm_hooks(&post_install_hooks(&user_opt.components))?,
+        )
+    };
+    config
+        .accepted_licenses(accepted_licenses)
+        .run_hooks(run_hooks)
         .install(user_opt.components)?;
 
-    let g_opts = GlobalOpts::get();
     if !g_opts.quiet {
         println!("\n{}\n", t!("install_finish_info"));
     }
@@ -85,7 +127,7 @@ pub(super) fn execute_installer(installer: &Installer) -> Result<()> {
     // However, it is not ideal when passing `--yes` when the user just want a quick install,
     // and might gets annoying when the user is doing a 'quick install' on WSL. (a VSCode
     // window will pop open on Windows)
-    if !g_opts.yes_to_all && common::confirm(t!("question_try_demo"), true)? {
+    if !g_opts.yes_to_all && lockfile.is_none() && common::confirm(t!("question_try_demo"), true)? {
         try_it::try_it(Some(&install_dir))?;
     }
 
@@ -111,14 +153,23 @@ struct CustomInstallOpt {
 impl CustomInstallOpt {
     /// Asking various questions and collect user input from CLI,
     /// then return user specified installation options.
-    fn collect_from_user(prefix: &Path, components: Vec<Component>) -> Result<Self> {
+    ///
+    /// If a `preset` (with its name) was given, its components will be chosen by default.
+    fn collect_from_user(
+        prefix: &Path,
+        components: Vec<Component>,
+        preset: Option<(&str, &Preset)>,
+    ) -> Result<Self> {
         if GlobalOpts::get().yes_to_all {
             return Ok(Self {
                 prefix: prefix.to_path_buf(),
-                components: default_component_choices(&components)
-                    .values()
-                    .map(|c| (*c).to_owned())
-                    .collect(),
+                components: without_conflicts(with_dependencies(
+                    &components,
+                    default_component_choices(&components, preset),
+                ))
+                .values()
+                .map(|c| (*c).to_owned())
+                .collect(),
             });
         }
 
@@ -147,7 +198,7 @@ impl CustomInstallOpt {
                 continue;
             }
 
-            let choices = read_component_selections(&components)?;
+            let choices = read_component_selections(&components, preset)?;
 
             show_confirmation(&install_dir, &choices)?;
 
@@ -168,15 +219,24 @@ impl CustomInstallOpt {
 fn read_install_dir_input(default: &str) -> Result<Option<String>> {
     let dir_input = common::question_str(t!("question_install_dir"), None, default)?;
     // verify path input before proceeding
-    if utils::is_root_dir(&dir_input) {
-        warn!("{}", t!("notify_root_dir"));
+    let issues = preflight::check_path(&utils::to_nomalized_abspath(&dir_input, None)?);
+    for issue in &issues {
+        warn!("{issue}");
+    }
+    if issues.iter().any(|i| i.is_fatal()) {
         Ok(None)
     } else {
         Ok(Some(dir_input))
     }
 }
 
-fn default_component_choices(components: &[Component]) -> ComponentChoices<'_> {
+fn default_component_choices<'c>(
+    components: &'c [Component],
+    preset: Option<(&str, &Preset)>,
+) -> ComponentChoices<'c> {
+    if let Some((_, preset)) = preset {
+        return common::preset_choices(components, preset);
+    }
     components
         .iter()
         .enumerate()
@@ -187,32 +247,45 @@ fn default_component_choices(components: &[Component]) -> ComponentChoices<'_> {
 /// Read user response of what set of components they want to install.
 ///
 /// Currently, there's only three options:
-/// 1. default
+/// 1. default (or the components of a `preset` if there is one)
 /// 2. everything
 /// 3. custom
-fn read_component_selections(components: &[Component]) -> Result<ComponentChoices<'_>> {
+fn read_component_selections<'c>(
+    components: &'c [Component],
+    preset: Option<(&str, &Preset)>,
+) -> Result<ComponentChoices<'c>> {
     let profile_choices = &[
-        t!("install_default"),
+        preset.map_or_else(
+            || t!("install_default"),
+            |(name, _)| t!("install_preset", name = name),
+        ),
         t!("install_everything"),
         t!("install_custom"),
     ];
     let choice = question_single_choice(t!("question_components_profile"), profile_choices, "1")?;
     let selection = match choice {
         // Default set
-        1 => default_component_choices(components),
-        // Full set, but exclude installed components
-        2 => components
-            .iter()
-            .enumerate()
-            .filter(|(_, c)| !c.installed)
-            .collect(),
-        // Customized set
-        3 => {
+        1 => without_conflicts(with_dependencies(
+            components,
+            default_component_choices(components, preset),
+        )),
+        // Full set, but exclude installed components,
+        // and the ones that cannot be installed along with the components before them.
+        2 => without_conflicts(with_dependencies(
+            components,
+            components
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| !c.installed)
+                .collect(),
+        )),
+        // Customized set, keep asking until there are no conflicts
+        3 => loop {
             let list_of_comps = ComponentListBuilder::new(components)
                 .show_desc(true)
                 .decorate(ComponentDecoration::InstalledOrRequired)
                 .build();
-            let default_ids = default_component_choices(components)
+            let default_ids = default_component_choices(components, preset)
                 .keys()
                 .map(|idx| (idx + 1).to_string())
                 .collect::<Vec<_>>()
@@ -228,12 +301,17 @@ fn read_component_selections(components: &[Component]) -> Result<ComponentChoice
 
             // convert the input indexes to `ComponentChoices`,
             // and we also need to add the `required` tools even if the user didn't choose it.
-            components
+            let selection = components
                 .iter()
                 .enumerate()
                 .filter(|(idx, c)| c.required || index_set.contains(&(idx + 1)))
-                .collect()
-        }
+                .collect();
+            // tools can't work without their dependencies, so select them as well
+            let selection = with_dependencies(components, selection);
+            if !common::has_conflicts(&selection) {
+                break selection;
+            }
+        },
         _ => unreachable!("out-of-range input should already be caught"),
     };
 
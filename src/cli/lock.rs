@@ -0,0 +1,24 @@
+//! Separated module to handle `export-lock` command in command line.
+
+use crate::fingerprint::InstallationRecord;
+use crate::lockfile::Lockfile;
+use crate::toolset_manifest::ToolsetManifest;
+
+use super::ManagerSubcommands;
+
+use anyhow::Result;
+use log::info;
+
+/// Execute `export-lock` command.
+pub(super) fn execute(subcommand: &ManagerSubcommands) -> Result<bool> {
+    let ManagerSubcommands::ExportLock { path } = subcommand else {
+        return Ok(false);
+    };
+
+    let record = InstallationRecord::load_from_install_dir()?;
+    let manifest = ToolsetManifest::load_from_install_dir()?;
+    Lockfile::from_installation(&record, &manifest).write(path)?;
+    info!("{}", t!("lockfile_exported", path = path.display()));
+
+    Ok(true)
+}
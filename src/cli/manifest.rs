@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::{Subcommand, ValueHint};
+
+use super::InstallerSubcommands;
+use crate::core::parser::lint::lint_toolset_manifest;
+
+#[derive(Subcommand, Debug)]
+pub(super) enum ManifestCommand {
+    /// Check a toolset manifest for mistakes, such as unknown keys or undefined tools
+    Lint {
+        /// Treat warnings as errors
+        #[arg(short = 'D', long)]
+        deny_warnings: bool,
+        /// Path to the toolset manifest file
+        #[arg(value_name = "FILE", value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+    },
+}
+
+impl ManifestCommand {
+    fn execute(&self) -> Result<()> {
+        match self {
+            Self::Lint {
+                deny_warnings,
+                file,
+            } => {
+                let report = lint_toolset_manifest(file)?;
+                let (errors, warnings) = (report.errors(), report.warnings());
+                eprint!("{report}");
+                if errors > 0 || (*deny_warnings && warnings > 0) {
+                    bail!(
+                        "'{}' has {errors} error(s) and {warnings} warning(s)",
+                        file.display()
+                    );
+                }
+                println!("'{}' is valid ({warnings} warning(s))", file.display());
+                Ok(())
+            }
+        }
+    }
+}
+
+pub(super) fn execute(cmd: &InstallerSubcommands) -> Result<bool> {
+    let InstallerSubcommands::Manifest { command } = cmd;
+
+    command.execute()?;
+    Ok(true)
+}
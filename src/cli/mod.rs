@@ -2,11 +2,17 @@
 
 mod common;
 mod component;
+mod history;
 mod install;
 mod list;
+mod lock;
+mod manifest;
+mod recover;
+mod rollback;
 mod tryit;
 mod uninstall;
 mod update;
+mod verify;
 
 use anyhow::{anyhow, bail, Result};
 use clap::{Parser, Subcommand, ValueHint};
@@ -59,6 +65,18 @@ pub struct Installer {
     /// Allow insecure connections when download packages from server.
     #[arg(short = 'k', long)]
     insecure: bool,
+    /// Accept the license of a tool without asking,
+    /// which is required for tools with licenses when `--yes` is passed.
+    #[arg(long, value_name = "TOOL")]
+    accept_license: Vec<String>,
+    /// Select the components of a preset declared in the manifest (such as `minimal` or `full`)
+    /// by default, instead of the default set of components.
+    #[arg(long, value_name = "NAME")]
+    preset: Option<String>,
+    /// Reproduce the installation recorded in a lockfile (exported by `export-lock`) without prompts,
+    /// which fails if anything in the toolkit no longer matches the lockfile.
+    #[arg(long, value_name = "PATH", conflicts_with = "preset", value_hint = ValueHint::FilePath)]
+    lock: Option<PathBuf>,
 
     /// Specify another language to display
     #[arg(short, long, value_name = "LANG", value_parser = Language::possible_values())]
@@ -81,6 +99,8 @@ pub struct Installer {
     /// Specify a path or url of manifest file that contains package source and various configurations.
     #[arg(long, value_name = "PATH or URL")]
     manifest: Option<PathOrUrl>,
+    #[command(subcommand)]
+    command: Option<InstallerSubcommands>,
 }
 
 #[derive(Debug, Clone)]
@@ -148,6 +168,16 @@ pub struct Manager {
     command: Option<ManagerSubcommands>,
 }
 
+macro_rules! return_if_executed {
+    ($($fn:expr),+) => {
+        $(
+            if $fn {
+                return Ok(());
+            }
+        )*
+    };
+}
+
 impl Installer {
     pub fn install_dir(&self) -> Option<&Path> {
         self.prefix.as_deref()
@@ -167,7 +197,28 @@ impl Installer {
             self.lang.as_deref(),
         )?;
 
-        install::execute_installer(self)
+        if let Some(subcmd) = &self.command {
+            return subcmd.execute();
+        }
+        common::self_update_if_too_old(install::execute_installer(self))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum InstallerSubcommands {
+    /// Utilities for toolset manifest authors
+    Manifest {
+        #[command(subcommand)]
+        command: manifest::ManifestCommand,
+    },
+}
+
+impl InstallerSubcommands {
+    fn execute(&self) -> Result<()> {
+        return_if_executed! {
+            manifest::execute(self)?
+        }
+        Ok(())
     }
 }
 
@@ -185,10 +236,11 @@ impl Manager {
             self.lang.as_deref(),
         )?;
 
-        let Some(subcmd) = &self.command else {
-            return ManagerSubcommands::from_interaction()?.execute();
+        let result = match &self.command {
+            Some(subcmd) => subcmd.execute(),
+            None => ManagerSubcommands::from_interaction()?.execute(),
         };
-        subcmd.execute()
+        common::self_update_if_too_old(result)
     }
 }
 
@@ -218,6 +270,14 @@ enum ManagerSubcommands {
         /// Update manager only
         #[arg(long, alias = "manager")]
         manager_only: bool,
+        /// Accept the license of a tool without asking,
+        /// which is required for tools with licenses when `--yes` is passed.
+        #[arg(long, value_name = "TOOL")]
+        accept_license: Vec<String>,
+        /// Keep a copy of the tools replaced by this update,
+        /// so that `rollback` could restore them without downloading them again.
+        #[arg(long)]
+        snapshot_tools: bool,
     },
     #[command(hide = true)]
     /// Show a list of available dist version or components
@@ -240,6 +300,47 @@ enum ManagerSubcommands {
         #[arg(long, alias = "keep-manager")]
         keep_self: bool,
     },
+    /// Check if any file of the installed components is missing or modified
+    Verify,
+    /// Reinstall the components that have missing or modified files
+    Repair {
+        /// Allow insecure connections when download packages from server.
+        #[arg(short = 'k', long)]
+        insecure: bool,
+        /// Accept the license of a tool without asking,
+        /// which is required for tools with licenses when `--yes` is passed.
+        #[arg(long, value_name = "TOOL")]
+        accept_license: Vec<String>,
+    },
+    /// Complete or undo an operation that was interrupted
+    Recover {
+        /// Undo the changes made by the interrupted operation, instead of completing it
+        #[arg(long)]
+        undo: bool,
+        /// Allow insecure connections when download packages from server.
+        #[arg(short = 'k', long)]
+        insecure: bool,
+    },
+    /// Roll the toolkit back to the version installed before the last update
+    Rollback {
+        /// Allow insecure connections when download packages from server.
+        #[arg(short = 'k', long)]
+        insecure: bool,
+    },
+    /// Write the exact versions and sources of the installed components into a lockfile
+    ///
+    /// The lockfile can be used to reproduce this installation with `--lock` in installer mode.
+    ExportLock {
+        /// Path of the lockfile to write
+        #[arg(value_name = "PATH", default_value = "rim.lock", value_hint = ValueHint::FilePath)]
+        path: PathBuf,
+    },
+    /// Show the operations made to this installation and the changes they made
+    History {
+        /// Only show the latest N operations
+        #[arg(short = 'n', long, value_name = "N")]
+        limit: Option<usize>,
+    },
     /// A subcommand to create a new Rust project template and let you start coding with it.
     TryIt {
         /// Specify another directory to create project template, defaulting to current directory.
@@ -248,16 +349,6 @@ enum ManagerSubcommands {
     },
 }
 
-macro_rules! return_if_executed {
-    ($($fn:expr),+) => {
-        $(
-            if $fn {
-                return Ok(());
-            }
-        )*
-    };
-}
-
 impl ManagerSubcommands {
     pub(crate) fn execute(&self) -> Result<()> {
         return_if_executed! {
@@ -266,6 +357,11 @@ impl ManagerSubcommands {
             list::execute(self)?,
             component::execute(self)?,
             uninstall::execute(self)?,
+            verify::execute(self)?,
+            recover::execute(self)?,
+            rollback::execute(self)?,
+            history::execute(self)?,
+            lock::execute(self)?,
             tryit::execute(self)?
         }
         Ok(())
@@ -313,7 +409,7 @@ impl ManagerSubcommands {
                             2 t!("skip_ssl_check") => { true }
                         }
                     );
-                    Some(Self::Update { insecure, toolkit_only: false, manager_only: false })
+                    Some(Self::Update { insecure, toolkit_only: false, manager_only: false, accept_license: vec![], snapshot_tools: false })
                 },
                 2 t!("uninstall") => { Some(Self::Uninstall { keep_self: false }) },
                 3 t!("cancel") => { None }
@@ -330,13 +426,13 @@ impl ManagerSubcommands {
             t!("ask_update_option"), 1,
             {
                 1 t!("update_all") => {
-                    Self::Update { insecure, toolkit_only: false, manager_only: false }
+                    Self::Update { insecure, toolkit_only: false, manager_only: false, accept_license: vec![], snapshot_tools: false }
                 },
                 2 t!("update_self_only") => {
-                    Self::Update { insecure, toolkit_only: false, manager_only: true }
+                    Self::Update { insecure, toolkit_only: false, manager_only: true, accept_license: vec![], snapshot_tools: false }
                 },
                 3 t!("update_toolkit_only") => {
-                    Self::Update { insecure, toolkit_only: true, manager_only: false }
+                    Self::Update { insecure, toolkit_only: true, manager_only: false, accept_license: vec![], snapshot_tools: false }
                 },
                 4 t!("back") => { return Ok(false) }
             }
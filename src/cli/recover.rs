@@ -0,0 +1,72 @@
+//! Separated module to handle `recover` command in command line.
+
+use crate::components::Component;
+use crate::core::install::InstallConfiguration;
+use crate::core::journal::{self, OperationKind};
+use crate::core::snapshot::Snapshot;
+use crate::get_installed_dir;
+use crate::toolset_manifest::ToolsetManifest;
+
+use super::ManagerSubcommands;
+
+use anyhow::{anyhow, Result};
+use log::info;
+
+/// Execute `recover` command.
+pub(super) fn execute(subcommand: &ManagerSubcommands) -> Result<bool> {
+    let ManagerSubcommands::Recover { undo, insecure } = subcommand else {
+        return Ok(false);
+    };
+
+    let root = get_installed_dir();
+    let Some(operation) = journal::unfinished_operation(root)? else {
+        info!("{}", t!("nothing_to_recover"));
+        return Ok(true);
+    };
+    if *undo {
+        journal::rollback(root)?;
+        info!("{}", t!("recover_undone"));
+        return Ok(true);
+    }
+
+    // Complete the operation by running it again, the tools that were already
+    // installed will be overwritten.
+    let snapshot = Snapshot::load(root);
+    let manifest = match (operation.kind, &snapshot) {
+        // rolling back uses the manifest of the previous toolkit
+        (OperationKind::Rollback, Some(snapshot)) => snapshot.manifest()?,
+        // updating uses the manifest of the new toolkit, which was kept along with the journal
+        (OperationKind::Update, _) => match journal::unfinished_manifest(root)? {
+            Some(manifest) => manifest,
+            None => ToolsetManifest::load_from_install_dir()?,
+        },
+        _ => ToolsetManifest::load_from_install_dir()?,
+    };
+    let components: Vec<Component> = manifest
+        .current_target_components(false)?
+        .into_iter()
+        .filter(|c| operation.components.contains(&c.name))
+        .collect();
+
+    let mut config = InstallConfiguration::new(root, &manifest)?
+        .rustup_dist_server(operation.rustup_dist_server)
+        .rustup_update_root(operation.rustup_update_root)
+        .insecure(*insecure)
+        .accepted_licenses(operation.accepted_licenses)
+        .run_hooks(operation.run_hooks);
+    if let Some((name, url)) = &operation.cargo_registry {
+        config = config.cargo_registry(name, url);
+    }
+    match operation.kind {
+        OperationKind::Install => config.install(components)?,
+        OperationKind::Update => config.update(components)?,
+        OperationKind::InstallComponents => config.install_components(components)?,
+        OperationKind::Repair => config.repair(components)?,
+        OperationKind::Rollback => {
+            config.rollback(snapshot.ok_or_else(|| anyhow!(t!("no_snapshot")))?)?;
+        }
+    }
+    info!("{}", t!("recover_completed"));
+
+    Ok(true)
+}
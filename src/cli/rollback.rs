@@ -0,0 +1,43 @@
+//! Separated module to handle `rollback` command in command line.
+
+use crate::core::install::InstallConfiguration;
+use crate::core::snapshot::Snapshot;
+use crate::fingerprint::InstallationRecord;
+use crate::get_installed_dir;
+
+use super::{common, ManagerSubcommands};
+
+use anyhow::Result;
+use log::info;
+
+/// Execute `rollback` command.
+pub(super) fn execute(subcommand: &ManagerSubcommands) -> Result<bool> {
+    let ManagerSubcommands::Rollback { insecure } = subcommand else {
+        return Ok(false);
+    };
+
+    let root = get_installed_dir();
+    let Some(snapshot) = Snapshot::load(root) else {
+        info!("{}", t!("no_snapshot"));
+        return Ok(true);
+    };
+    let current = InstallationRecord::load_from_install_dir()?;
+    let previous = snapshot.record()?;
+    let unknown = || "?".to_string();
+    let prompt = t!(
+        "question_rollback",
+        current_version = current.version.unwrap_or_else(unknown),
+        target_version = previous.version.unwrap_or_else(unknown)
+    );
+    if !common::confirm(prompt, false)? {
+        return Ok(true);
+    }
+
+    let manifest = snapshot.manifest()?;
+    InstallConfiguration::new(root, &manifest)?
+        .insecure(*insecure)
+        .rollback(snapshot)?;
+    info!("{}", t!("rollback_completed"));
+
+    Ok(true)
+}
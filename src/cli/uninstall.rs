@@ -29,7 +29,8 @@ pub(super) fn execute(subcommand: &ManagerSubcommands) -> Result<bool> {
         return Ok(true);
     }
 
-    config.uninstall(!keep_self)?;
+    let run_hooks = common::confirm_hooks(&config.hook_descriptions(None))?;
+    config.run_hooks(run_hooks).uninstall(!keep_self)?;
 
     Ok(true)
 }
@@ -4,11 +4,12 @@ use std::collections::HashSet;
 use std::path::Path;
 use url::Url;
 
-use crate::components::Component;
+use crate::components::{post_install_hooks, Component};
+use crate::core::registry;
 use crate::core::toolkit::Toolkit;
 use crate::core::update::UpdateOpt;
 use crate::toolkit::latest_installable_toolkit;
-use crate::toolset_manifest::get_toolset_manifest;
+use crate::toolset_manifest::{get_toolset_manifest, ToolInfo};
 use crate::InstallConfiguration;
 
 use super::common::{ComponentChoices, ComponentDecoration, ComponentListBuilder, VersionDiffMap};
@@ -19,6 +20,8 @@ pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<bool> {
         toolkit_only,
         manager_only,
         insecure,
+        accept_license,
+        snapshot_tools,
     } = cmd
     else {
         return Ok(false);
@@ -26,7 +29,9 @@ pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<bool> {
 
     let update_opt = UpdateOpt::new().insecure(*insecure);
     if !manager_only {
-        update_opt.update_toolkit(|path| update_toolkit_(path, *insecure))?;
+        update_opt.update_toolkit(|path| {
+            update_toolkit_(path, *insecure, accept_license, *snapshot_tools)
+        })?;
     }
     if !toolkit_only {
         update_opt.self_update()?;
@@ -35,7 +40,12 @@ pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<bool> {
     Ok(true)
 }
 
-fn update_toolkit_(install_dir: &Path, insecure: bool) -> Result<()> {
+fn update_toolkit_(
+    install_dir: &Path,
+    insecure: bool,
+    accept_license: &[String],
+    snapshot_tools: bool,
+) -> Result<()> {
     let Some(installed) = Toolkit::installed(false)? else {
         info!("{}", t!("no_toolkit_installed"));
         return Ok(());
@@ -76,12 +86,22 @@ fn update_toolkit_(install_dir: &Path, insecure: bool) -> Result<()> {
         )
     );
 
-    let updater = ComponentsUpdater::new(&installed.components, &new_components);
+    let config = InstallConfiguration::new(install_dir, &manifest)?;
+    let updater = ComponentsUpdater::new(&installed.components, &new_components, |name, req| {
+        config.resolve_cargo_tool_version(name, req).ok()
+    });
     // let user choose if they want to update installed component only, or want to select more components to install
     if let UpdateOption::Yes(components) = updater.get_user_choices()? {
         // install update for selected components
-        let config = InstallConfiguration::new(install_dir, &manifest)?;
-        config.update(components.into_values().cloned().collect())
+        let components: Vec<Component> = components.into_values().cloned().collect();
+        let licenses = config.licenses_to_accept(&components);
+        let accepted_licenses = common::accept_licenses(&licenses, accept_license)?;
+        let run_hooks = common::confirm_hooks(&post_install_hooks(&components))?;
+        config
+            .accepted_licenses(accepted_licenses)
+            .run_hooks(run_hooks)
+            .snapshot_tools(snapshot_tools)
+            .update(components)
     } else {
         Ok(())
     }
@@ -95,11 +115,18 @@ enum UpdateOption<'c> {
 struct ComponentsUpdater<'c> {
     target: &'c [Component],
     version_diff: VersionDiffMap<'c>,
+    /// Names of the components whose installed version differs from the target one.
+    changed: HashSet<&'c str>,
 }
 
 impl<'c> ComponentsUpdater<'c> {
-    fn new(installed: &'c [Component], target: &'c [Component]) -> Self {
-        let version_diff = target
+    /// `resolve` is used to get the newest version of a cargo tool that matches a
+    /// version requirement (such as `^0.9`), given the tool's name and the requirement.
+    fn new<F>(installed: &'c [Component], target: &'c [Component], resolve: F) -> Self
+    where
+        F: Fn(&str, &str) -> Option<String>,
+    {
+        let version_diff: VersionDiffMap<'c> = target
             .iter()
             .map(|c| {
                 let installed_version = installed
@@ -109,9 +136,27 @@ impl<'c> ComponentsUpdater<'c> {
                 (c.name.as_str(), (installed_version, c.version.as_deref()))
             })
             .collect();
+        let changed = target
+            .iter()
+            .filter(|c| {
+                let (from, to) = version_diff[c.name.as_str()];
+                let is_cargo_tool = c
+                    .tool_installer
+                    .as_ref()
+                    .is_some_and(ToolInfo::is_cargo_tool);
+                match (from, to) {
+                    (Some(from), Some(to)) if is_cargo_tool => {
+                        registry::is_version_changed(from, to, || resolve(&c.name, to))
+                    }
+                    _ => from != to,
+                }
+            })
+            .map(|c| c.name.as_str())
+            .collect();
         Self {
             target,
             version_diff,
+            changed,
         }
     }
 
@@ -119,11 +164,9 @@ impl<'c> ComponentsUpdater<'c> {
     // and having different version.
     // Note that we don't check if the new version is actually "newer" than the installed version,
     // it is intended to prevent a scenario where a component needs to be rollback in a new toolkit.
+    // For cargo tools with version requirements, check `registry::is_version_changed` instead.
     fn component_names_with_diff_version(&self) -> HashSet<&'c str> {
-        self.version_diff
-            .iter()
-            .filter_map(|(name, (from, to))| (from != to).then_some(*name))
-            .collect()
+        self.changed.clone()
     }
 
     fn get_user_choices(&self) -> Result<UpdateOption<'c>> {
@@ -154,23 +197,30 @@ impl<'c> ComponentsUpdater<'c> {
             .map(|idx| (idx + 1).to_string())
             .collect::<Vec<_>>()
             .join(" ");
-        let input = common::question_multi_choices(
-            t!("select_components_to_update"),
-            &choices,
-            defult_choices,
-        )?;
-
-        // convert input vec to set for faster lookup
-        // Note: user input index are started from 1.
-        let index_set: HashSet<usize> = input.into_iter().collect();
-
-        // convert the input indexes to `ComponentChoices`
-        Ok(self
-            .target
-            .iter()
-            .enumerate()
-            .filter(|(idx, _)| index_set.contains(&(idx + 1)))
-            .collect())
+        // keep asking until there are no conflicts
+        loop {
+            let input = common::question_multi_choices(
+                t!("select_components_to_update"),
+                &choices,
+                &defult_choices,
+            )?;
+
+            // convert input vec to set for faster lookup
+            // Note: user input index are started from 1.
+            let index_set: HashSet<usize> = input.into_iter().collect();
+
+            // convert the input indexes to `ComponentChoices`
+            let selection = self
+                .target
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| index_set.contains(&(idx + 1)))
+                .collect();
+            let selection = common::with_dependencies(self.target, selection);
+            if !common::has_conflicts(&selection) {
+                return Ok(selection);
+            }
+        }
     }
 
     // recursively ask for user input
@@ -0,0 +1,87 @@
+//! Separated module to handle `verify` and `repair` commands in command line.
+
+use crate::components::{self, post_install_hooks, Component};
+use crate::core::install::InstallConfiguration;
+use crate::fingerprint::InstallationRecord;
+use crate::installed_files::InstalledFiles;
+use crate::toolset_manifest::ToolsetManifest;
+
+use super::{common, ManagerSubcommands};
+
+use anyhow::{bail, Result};
+use log::{info, warn};
+
+/// Execute `verify` or `repair` command.
+pub(super) fn execute(subcommand: &ManagerSubcommands) -> Result<bool> {
+    match subcommand {
+        ManagerSubcommands::Verify => verify()?,
+        ManagerSubcommands::Repair {
+            insecure,
+            accept_license,
+        } => repair(*insecure, accept_license)?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Check the files of every installed tool, report the problems found,
+/// and return the names of the tools that have any.
+fn broken_tools(record: &InstallationRecord) -> Result<Vec<String>> {
+    let files = InstalledFiles::load_from_root(&record.root)?;
+    let mut broken = vec![];
+    for name in record.installed_tools() {
+        match files.check_tool(&record.root, name) {
+            None => warn!("{}", t!("verify_no_file_records", name = name)),
+            Some(issues) if issues.is_empty() => {
+                info!("{}", t!("verify_component_ok", name = name));
+            }
+            Some(issues) => {
+                warn!("{}", t!("verify_component_broken", name = name));
+                for issue in issues {
+                    warn!("  {issue}");
+                }
+                broken.push(name.to_string());
+            }
+        }
+    }
+    Ok(broken)
+}
+
+fn verify() -> Result<()> {
+    let record = InstallationRecord::load_from_install_dir()?;
+    let broken = broken_tools(&record)?;
+    if !broken.is_empty() {
+        bail!(t!("verify_failed", list = broken.join(", ")));
+    }
+    Ok(())
+}
+
+fn repair(insecure: bool, accept_license: &[String]) -> Result<()> {
+    let record = InstallationRecord::load_from_install_dir()?;
+    let broken = broken_tools(&record)?;
+    if broken.is_empty() {
+        info!("{}", t!("nothing_to_repair"));
+        return Ok(());
+    }
+
+    let manifest = ToolsetManifest::load_from_install_dir()?;
+    let components: Vec<Component> = components::all_components_from_installation(&record)?
+        .into_iter()
+        .filter(|c| broken.contains(&c.name))
+        .collect();
+    if let Some(missing) = broken
+        .iter()
+        .find(|name| !components.iter().any(|c| &c.name == *name))
+    {
+        bail!("unable to repair '{missing}' because it's no longer available in the toolkit");
+    }
+
+    let config = InstallConfiguration::new(&record.root, &manifest)?.insecure(insecure);
+    let licenses = config.licenses_to_accept(&components);
+    let accepted_licenses = common::accept_licenses(&licenses, accept_license)?;
+    let run_hooks = common::confirm_hooks(&post_install_hooks(&components))?;
+    config
+        .accepted_licenses(accepted_licenses)
+        .run_hooks(run_hooks)
+        .repair(components)
+}
@@ -1,14 +1,14 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     sync::atomic::{AtomicU32, Ordering},
 };
 
 use crate::{
     fingerprint::InstallationRecord,
     setter,
-    toolset_manifest::{ToolInfo, ToolMap, ToolsetManifest},
+    toolset_manifest::{Hooks, ToolInfo, ToolMap, ToolsetManifest},
 };
 
 static COMPONENTS_COUNTER: AtomicU32 = AtomicU32::new(0);
@@ -24,7 +24,14 @@ pub struct Component {
     pub required: bool,
     pub optional: bool,
     pub tool_installer: Option<ToolInfo>,
+    /// Names of other components that cannot be installed along with this one.
+    #[serde(default)]
+    pub conflicts: Vec<String>,
     pub is_toolchain_component: bool,
+    /// Indicates whether this is a (cross compilation) target of the toolchain,
+    /// which is installed with `rustup`'s `--target` option instead of `--component`.
+    #[serde(default)]
+    pub is_target: bool,
     /// Indicates whether this component was already installed or not.
     pub installed: bool,
 }
@@ -41,7 +48,9 @@ impl Component {
             required: false,
             optional: false,
             tool_installer: None,
+            conflicts: vec![],
             is_toolchain_component: false,
+            is_target: false,
             installed: false,
         };
         COMPONENTS_COUNTER.fetch_add(1, Ordering::SeqCst);
@@ -53,9 +62,17 @@ impl Component {
     setter!(optional(self, bool));
     setter!(installed(self, bool));
     setter!(is_toolchain_component(self, bool));
+    setter!(is_target(self, bool));
+    setter!(conflicts(self, Vec<String>));
     setter!(group_name(self, group: Option<&str>) { group.map(ToOwned::to_owned) });
     setter!(tool_installer(self, installer: &ToolInfo) { Some(installer.clone()) });
     setter!(version(self, version: Option<&str>) { version.map(ToOwned::to_owned) });
+
+    /// Check if this is the (mocked) component that represents the rust toolchain itself,
+    /// rather than one of the optional components or targets of it.
+    pub fn is_rust_toolchain(&self) -> bool {
+        self.is_toolchain_component && !self.optional
+    }
 }
 
 /// Get a combined list of tools and toolchain components in Vec<[Component]> format,
@@ -74,13 +91,26 @@ pub(crate) fn all_components_from_installation(
 
     // components that are installed by rim previously.
     let installed_toolchain = record.installed_toolchain().map(|(name, _)| name);
+    let installed_tc_components: HashSet<&str> = record
+        .installed_toolchain()
+        .map(|(_, components)| components.collect())
+        .unwrap_or_default();
+    let installed_targets: HashSet<&str> = record.installed_targets().collect();
     let installed_tools: HashSet<&str> = record.installed_tools().collect();
 
     for comp in &mut full_components {
+        if comp.is_target {
+            if let Some(tc) = installed_toolchain {
+                comp.version = Some(tc.into());
+                comp.installed = installed_targets.contains(comp.name.as_str());
+            }
+            continue;
+        }
         if comp.is_toolchain_component {
             if let Some(tc) = installed_toolchain {
                 comp.version = Some(tc.into());
-                comp.installed = true;
+                comp.installed = comp.is_rust_toolchain()
+                    || installed_tc_components.contains(comp.name.as_str());
             }
             continue;
         }
@@ -105,3 +135,272 @@ pub fn component_list_to_tool_map(list: Vec<&Component>) -> ToolMap {
         })
         .collect()
 }
+
+/// Collect the names of every (direct or indirect) dependency of the `selected` components,
+/// looking up the dependency information from the full list of components (`all`).
+pub fn dependencies_of<'c, I>(all: &'c [Component], selected: I) -> HashSet<&'c str>
+where
+    I: IntoIterator<Item = &'c Component>,
+{
+    let by_name: HashMap<&str, &Component> = all.iter().map(|c| (c.name.as_str(), c)).collect();
+    let mut stack: Vec<&Component> = selected.into_iter().collect();
+    let mut deps = HashSet::new();
+
+    while let Some(comp) = stack.pop() {
+        let Some(tool_info) = &comp.tool_installer else {
+            continue;
+        };
+        for dep in tool_info.dependencies() {
+            if let Some(dep_comp) = by_name.get(dep.as_str()) {
+                if deps.insert(dep_comp.name.as_str()) {
+                    stack.push(dep_comp);
+                }
+            }
+        }
+    }
+    deps
+}
+
+/// Find the pairs of components in the given list that cannot be installed together.
+pub fn conflicting_pairs<'c, I>(components: I) -> Vec<(&'c str, &'c str)>
+where
+    I: IntoIterator<Item = &'c Component>,
+{
+    let components = components.into_iter().collect::<Vec<_>>();
+    let mut pairs = vec![];
+    for (idx, a) in components.iter().enumerate() {
+        for b in &components[idx + 1..] {
+            if a.conflicts.contains(&b.name) || b.conflicts.contains(&a.name) {
+                pairs.push((a.name.as_str(), b.name.as_str()));
+            }
+        }
+    }
+    pairs
+}
+
+/// Make sure there are no components in the list that cannot be installed together.
+///
+/// # Errors
+/// Return `Err` listing every pair of conflicting components if there are any.
+pub(crate) fn check_conflicts(components: &[Component]) -> Result<()> {
+    ensure_no_conflicting_pairs(&conflicting_pairs(components))
+}
+
+/// Make sure none of the `components` conflicts with the `installed` tools,
+/// which should be checked when installing more components into an existing installation.
+///
+/// # Errors
+/// Return `Err` listing every pair of a component and the installed tool it conflicts with.
+pub(crate) fn check_conflicts_with_installed<'a, I>(
+    components: &[Component],
+    installed: I,
+) -> Result<()>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let installed = installed.into_iter().collect::<Vec<_>>();
+    let pairs = components
+        .iter()
+        .filter(|c| !installed.contains(&c.name.as_str()))
+        .flat_map(|c| {
+            installed
+                .iter()
+                .filter(|name| c.conflicts.iter().any(|cf| cf == *name))
+                .map(|name| (c.name.as_str(), *name))
+        })
+        .collect::<Vec<_>>();
+    ensure_no_conflicting_pairs(&pairs)
+}
+
+fn ensure_no_conflicting_pairs(pairs: &[(&str, &str)]) -> Result<()> {
+    if !pairs.is_empty() {
+        let list = pairs
+            .iter()
+            .map(|(a, b)| format!("'{a}' and '{b}'"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        bail!("the following components cannot be installed together: {list}");
+    }
+    Ok(())
+}
+
+/// Describe the post-install hooks of the given components in human readable lines,
+/// which should be confirmed by the user before running them.
+pub fn post_install_hooks<'c, I>(components: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'c Component>,
+{
+    components
+        .into_iter()
+        .filter_map(|c| Some((&c.name, c.tool_installer.as_ref()?.hooks()?)))
+        .flat_map(|(name, hooks)| Hooks::describe(name, "post-install", &hooks.post_install))
+        .collect()
+}
+
+/// Sort the tools so that every tool comes after the tools it depends on,
+/// while keeping the original order as much as possible.
+///
+/// Dependencies that are not in the given map will be ignored,
+/// as they might already been installed.
+///
+/// # Errors
+/// Return `Err` if there are circular dependencies.
+pub(crate) fn sort_by_dependencies(tools: &ToolMap) -> Result<ToolMap> {
+    fn visit<'t>(
+        name: &'t str,
+        tools: &'t ToolMap,
+        visiting: &mut Vec<&'t str>,
+        sorted: &mut ToolMap,
+    ) -> Result<()> {
+        if sorted.contains_key(name) {
+            return Ok(());
+        }
+        if let Some(pos) = visiting.iter().position(|n| *n == name) {
+            let mut cycle = visiting[pos..].to_vec();
+            cycle.push(name);
+            bail!("circular dependency detected: {}", cycle.join(" -> "));
+        }
+        let Some((key, info)) = tools.get_key_value(name) else {
+            return Ok(());
+        };
+
+        visiting.push(name);
+        for dep in info.dependencies() {
+            visit(dep, tools, visiting, sorted)?;
+        }
+        visiting.pop();
+
+        sorted.insert(key.clone(), info.clone());
+        Ok(())
+    }
+
+    let mut sorted = ToolMap::new();
+    for name in tools.keys() {
+        visit(name, tools, &mut vec![], &mut sorted)?;
+    }
+    Ok(sorted)
+}
+
+/// Get the names of the tools that can only be installed after the rust toolchain,
+/// which are the ones installed using `cargo`, and the ones depending on them.
+///
+/// `tools` should already be sorted with [`sort_by_dependencies`].
+pub(crate) fn tools_requiring_rust(tools: &ToolMap) -> HashSet<&str> {
+    let mut requiring_rust = HashSet::new();
+    for (name, info) in tools {
+        if info.is_cargo_tool()
+            || info
+                .dependencies()
+                .iter()
+                .any(|dep| requiring_rust.contains(dep.as_str()))
+        {
+            requiring_rust.insert(name.as_str());
+        }
+    }
+    requiring_rust
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tools_from_toml(input: &str) -> ToolMap {
+        toml::from_str(input).unwrap()
+    }
+
+    #[test]
+    fn dependencies_are_sorted_first() {
+        let tools = tools_from_toml(
+            r#"
+a = { ver = "0.1.0", depends-on = ["b", "c"] }
+b = { ver = "0.1.0", depends-on = ["c"] }
+c = "0.1.0"
+d = { ver = "0.1.0", depends-on = ["not-selected"] }
+"#,
+        );
+        let sorted = sort_by_dependencies(&tools).unwrap();
+        assert_eq!(sorted.keys().collect::<Vec<_>>(), ["c", "b", "a", "d"]);
+    }
+
+    #[test]
+    fn dependencies_on_cargo_tools() {
+        let tools = tools_from_toml(
+            r#"
+a = { path = "/path/to/a", depends-on = ["b"] }
+b = { ver = "0.1.0", depends-on = ["c"] }
+c = { path = "/path/to/c" }
+d = { path = "/path/to/d", depends-on = ["a"] }
+e = { path = "/path/to/e" }
+"#,
+        );
+        let sorted = sort_by_dependencies(&tools).unwrap();
+        let requiring_rust = tools_requiring_rust(&sorted);
+        // `a` needs to wait for the cargo tool `b`, so does `d` which depends on `a`
+        assert_eq!(requiring_rust, HashSet::from(["a", "b", "d"]));
+    }
+
+    #[test]
+    fn circular_dependencies() {
+        let tools = tools_from_toml(
+            r#"
+a = { ver = "0.1.0", depends-on = ["b"] }
+b = { ver = "0.1.0", depends-on = ["a"] }
+"#,
+        );
+        let err = sort_by_dependencies(&tools).unwrap_err();
+        assert_eq!(err.to_string(), "circular dependency detected: a -> b -> a");
+    }
+
+    #[test]
+    fn conflicting_components() {
+        let all = [
+            Component::new("a", "").conflicts(vec!["b".into()]),
+            Component::new("b", "").conflicts(vec!["a".into()]),
+            Component::new("c", ""),
+        ];
+
+        assert_eq!(conflicting_pairs(&all), [("a", "b")]);
+        assert!(check_conflicts(&all[1..]).is_ok());
+        let err = check_conflicts(&all).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the following components cannot be installed together: 'a' and 'b'"
+        );
+    }
+
+    #[test]
+    fn conflicting_with_installed() {
+        let selected = [
+            Component::new("mold", "").conflicts(vec!["lld".into(), "sold".into()]),
+            Component::new("c", ""),
+        ];
+
+        assert!(check_conflicts_with_installed(&selected, ["c", "d"]).is_ok());
+        let err = check_conflicts_with_installed(&selected, ["lld", "d"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the following components cannot be installed together: 'mold' and 'lld'"
+        );
+        // a conflicting component that is already installed is not being installed again
+        assert!(check_conflicts_with_installed(&selected, ["mold", "lld"]).is_ok());
+    }
+
+    #[test]
+    fn collect_indirect_dependencies() {
+        let tools = tools_from_toml(
+            r#"
+a = { ver = "0.1.0", depends-on = ["b"] }
+b = { ver = "0.1.0", depends-on = ["c"] }
+c = "0.1.0"
+d = "0.1.0"
+"#,
+        );
+        let all: Vec<Component> = tools
+            .iter()
+            .map(|(name, info)| Component::new(name, "").tool_installer(info))
+            .collect();
+
+        let deps = dependencies_of(&all, &all[..1]);
+        assert_eq!(deps, HashSet::from(["b", "c"]));
+    }
+}
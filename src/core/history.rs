@@ -0,0 +1,341 @@
+//! A log of the operations made to an installation, such as installs, updates and uninstalls,
+//! along with the changes they made, so it's possible to tell what changed and when.
+//!
+//! The history is kept in a file under the installation directory, one entry per line in JSON.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use super::journal::OperationKind;
+use super::parser::fingerprint::InstallationRecord;
+use crate::utils;
+
+const FILENAME: &str = ".history";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HistoryOperation {
+    Install,
+    Update,
+    InstallComponents,
+    UninstallComponents,
+    Repair,
+    Rollback,
+    Uninstall,
+}
+
+impl From<OperationKind> for HistoryOperation {
+    fn from(kind: OperationKind) -> Self {
+        match kind {
+            OperationKind::Install => Self::Install,
+            OperationKind::Update => Self::Update,
+            OperationKind::InstallComponents => Self::InstallComponents,
+            OperationKind::Repair => Self::Repair,
+            OperationKind::Rollback => Self::Rollback,
+        }
+    }
+}
+
+impl fmt::Display for HistoryOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Install => "install",
+            Self::Update => "update",
+            Self::InstallComponents => "install-components",
+            Self::UninstallComponents => "uninstall-components",
+            Self::Repair => "repair",
+            Self::Rollback => "rollback",
+            Self::Uninstall => "uninstall",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A change of a component made by an operation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "change", rename_all = "kebab-case")]
+pub enum ComponentChange {
+    Installed {
+        name: String,
+        version: Option<String>,
+    },
+    Removed {
+        name: String,
+        version: Option<String>,
+    },
+    Changed {
+        name: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+impl fmt::Display for ComponentChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ver = |v: &Option<String>| v.clone().unwrap_or_else(|| "?".into());
+        match self {
+            Self::Installed { name, version } => write!(f, "+ {name} {}", ver(version)),
+            Self::Removed { name, version } => write!(f, "- {name} {}", ver(version)),
+            Self::Changed { name, from, to } => {
+                write!(f, "~ {name} {} -> {}", ver(from), ver(to))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum Outcome {
+    Succeeded,
+    Failed { reason: String },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HistoryEntry {
+    /// The time when the operation started, in RFC 3339 format.
+    pub time: String,
+    pub operation: HistoryOperation,
+    /// Version of the toolkit before the operation.
+    pub toolkit_from: Option<String>,
+    /// Version of the toolkit after the operation.
+    pub toolkit_to: Option<String>,
+    #[serde(default)]
+    pub changes: Vec<ComponentChange>,
+    pub outcome: Outcome,
+}
+
+impl fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ver = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".into());
+        write!(f, "{}  {}", self.time, self.operation)?;
+        if self.toolkit_from != self.toolkit_to {
+            write!(
+                f,
+                "  {} -> {}",
+                ver(&self.toolkit_from),
+                ver(&self.toolkit_to)
+            )?;
+        } else if let Some(ver) = &self.toolkit_to {
+            write!(f, "  {ver}")?;
+        }
+        match &self.outcome {
+            Outcome::Succeeded => write!(f, "  {}", t!("history_succeeded"))?,
+            Outcome::Failed { reason } => write!(f, "  {}: {reason}", t!("history_failed"))?,
+        }
+        for change in &self.changes {
+            write!(f, "\n    {change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Versions of the installed components, keyed by their names.
+type Snapshot = BTreeMap<String, Option<String>>;
+
+fn snapshot(record: &InstallationRecord) -> Snapshot {
+    let mut installed = Snapshot::new();
+    if let Some((tc_ver, components)) = record.installed_toolchain() {
+        installed.insert("rust".into(), Some(tc_ver.to_string()));
+        for name in components.chain(record.installed_targets()) {
+            installed.insert(name.to_string(), Some(tc_ver.to_string()));
+        }
+    }
+    for name in record.installed_tools() {
+        installed.insert(
+            name.to_string(),
+            record.get_tool_version(name).map(ToOwned::to_owned),
+        );
+    }
+    installed
+}
+
+fn diff(before: &Snapshot, after: &Snapshot) -> Vec<ComponentChange> {
+    let removed = before
+        .iter()
+        .filter(|(name, _)| !after.contains_key(*name))
+        .map(|(name, version)| ComponentChange::Removed {
+            name: name.clone(),
+            version: version.clone(),
+        });
+    let installed_or_changed = after
+        .iter()
+        .filter_map(|(name, to)| match before.get(name) {
+            None => Some(ComponentChange::Installed {
+                name: name.clone(),
+                version: to.clone(),
+            }),
+            Some(from) if from != to => Some(ComponentChange::Changed {
+                name: name.clone(),
+                from: from.clone(),
+                to: to.clone(),
+            }),
+            Some(_) => None,
+        });
+    removed.chain(installed_or_changed).collect()
+}
+
+/// Keeps the state of an installation before an operation, in order to add an entry
+/// with the changes made by the operation into the history once it's done.
+pub(crate) struct HistoryTracker {
+    time: String,
+    operation: HistoryOperation,
+    toolkit_from: Option<String>,
+    before: Snapshot,
+}
+
+impl HistoryTracker {
+    pub(crate) fn start(operation: HistoryOperation, record: &InstallationRecord) -> Self {
+        Self {
+            time: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            operation,
+            toolkit_from: record.version.clone(),
+            before: snapshot(record),
+        }
+    }
+
+    /// Add an entry for the operation into the history of `record`'s installation,
+    /// `result` is the result of the operation.
+    ///
+    /// Failing to write history does not fail the operation, so errors are only logged.
+    pub(crate) fn finish(self, record: &InstallationRecord, result: &Result<()>) {
+        let entry = HistoryEntry {
+            time: self.time,
+            operation: self.operation,
+            toolkit_from: self.toolkit_from,
+            toolkit_to: record.version.clone(),
+            changes: diff(&self.before, &snapshot(record)),
+            outcome: match result {
+                Ok(()) => Outcome::Succeeded,
+                Err(e) => Outcome::Failed {
+                    reason: e.to_string(),
+                },
+            },
+        };
+        if let Err(e) = append(&record.root, &entry) {
+            warn!("{}", t!("history_write_failed", reason = e));
+        }
+    }
+}
+
+fn append(root: &Path, entry: &HistoryEntry) -> Result<()> {
+    let path = root.join(FILENAME);
+    let line = serde_json::to_string(entry)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("unable to open history file '{}'", path.display()))?;
+    writeln!(file, "{line}")?;
+    Ok(())
+}
+
+/// Load the history of the installation in `root`, from the oldest entry to the newest.
+pub fn load(root: &Path) -> Result<Vec<HistoryEntry>> {
+    let path = root.join(FILENAME);
+    if !path.is_file() {
+        return Ok(vec![]);
+    }
+    let content = utils::read_to_string("history", &path)?;
+    let entries = content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::TomlParser;
+
+    #[test]
+    fn changes_between_records() {
+        let before = r#"
+version = "0.1.0"
+root = '/path/to/something'
+
+[rust]
+version = "1.80.0"
+components = ["rustfmt"]
+
+[tools]
+a = { kind = "custom", version = "1.0.0", paths = [] }
+b = { kind = "custom", paths = [] }
+"#;
+        let after = r#"
+version = "0.2.0"
+root = '/path/to/something'
+
+[rust]
+version = "1.81.0"
+components = ["rustfmt"]
+
+[tools]
+a = { kind = "custom", version = "1.0.0", paths = [] }
+c = { kind = "custom", version = "0.3.0", paths = [] }
+"#;
+        let before = InstallationRecord::from_str(before).unwrap();
+        let after = InstallationRecord::from_str(after).unwrap();
+
+        let changes = diff(&snapshot(&before), &snapshot(&after));
+        assert_eq!(
+            changes,
+            [
+                ComponentChange::Removed {
+                    name: "b".into(),
+                    version: None
+                },
+                ComponentChange::Installed {
+                    name: "c".into(),
+                    version: Some("0.3.0".into())
+                },
+                ComponentChange::Changed {
+                    name: "rust".into(),
+                    from: Some("1.80.0".into()),
+                    to: Some("1.81.0".into())
+                },
+                ComponentChange::Changed {
+                    name: "rustfmt".into(),
+                    from: Some("1.80.0".into()),
+                    to: Some("1.81.0".into())
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn append_and_load() {
+        let root = tempfile::tempdir().unwrap();
+        let mut record = InstallationRecord {
+            root: root.path().to_path_buf(),
+            ..Default::default()
+        };
+        let tracker = HistoryTracker::start(HistoryOperation::Install, &record);
+        record.version = Some("0.1.0".into());
+        tracker.finish(&record, &Ok(()));
+
+        let tracker = HistoryTracker::start(HistoryOperation::Update, &record);
+        tracker.finish(&record, &Err(anyhow::anyhow!("network error")));
+
+        let history = load(root.path()).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].operation, HistoryOperation::Install);
+        assert_eq!(history[0].toolkit_from, None);
+        assert_eq!(history[0].toolkit_to.as_deref(), Some("0.1.0"));
+        assert_eq!(history[0].outcome, Outcome::Succeeded);
+        assert_eq!(
+            history[1].outcome,
+            Outcome::Failed {
+                reason: "network error".into()
+            }
+        );
+    }
+}
@@ -1,26 +1,36 @@
 use super::{
-    components::{component_list_to_tool_map, Component},
+    components::{
+        check_conflicts, check_conflicts_with_installed, component_list_to_tool_map,
+        sort_by_dependencies, tools_requiring_rust, Component,
+    },
     directories::RimDir,
+    history::HistoryTracker,
+    journal::{Journal, Operation, OperationKind, Step},
     parser::{
         cargo_config::CargoConfig,
         fingerprint::{InstallationRecord, ToolRecord},
-        toolset_manifest::{ToolInfo, ToolsetManifest},
+        installed_files::{self, InstalledFiles},
+        toolset_manifest::{License, ToolInfo, ToolsetManifest, Variables},
         TomlParser,
     },
+    preflight, registry,
     rustup::ToolchainInstaller,
-    tools::Tool,
+    snapshot::Snapshot,
+    tools::{run_hooks, Tool, ToolKind},
+    uninstall::UninstallConfiguration,
     CARGO_HOME, RUSTUP_DIST_SERVER, RUSTUP_HOME, RUSTUP_UPDATE_ROOT,
 };
 use crate::{
-    core::os::add_to_path,
+    core::os::{add_to_path, set_env_vars},
     setter,
     toolset_manifest::ToolMap,
-    utils::{self, Extractable, Progress},
+    utils::{self, Extractable, HashAlgorithm, Progress},
 };
 use anyhow::{anyhow, bail, Context, Result};
-use log::info;
+use indexmap::IndexMap;
+use log::{debug, info, warn};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     path::{Path, PathBuf},
 };
 use tempfile::TempDir;
@@ -79,6 +89,17 @@ pub struct InstallConfiguration<'a> {
     pub(crate) progress_indicator: Option<Progress<'a>>,
     manifest: &'a ToolsetManifest,
     insecure: bool,
+    /// Whether to run the hooks of tools, which should be confirmed by the user.
+    run_hooks: bool,
+    /// Names of the tools whose license was accepted by the user.
+    accepted_licenses: Vec<String>,
+    /// Whether to overwrite the tools that are already installed, used when repairing.
+    force: bool,
+    /// Whether to keep a copy of the files of the tools replaced by an update,
+    /// so that they could be restored without downloading them again when rolling back.
+    snapshot_tools: bool,
+    /// The journal of the current operation, which is used to roll back a failed operation.
+    journal: Option<Journal>,
 }
 
 impl RimDir for InstallConfiguration<'_> {
@@ -99,6 +120,11 @@ impl<'a> InstallConfiguration<'a> {
             progress_indicator: None,
             manifest,
             insecure: false,
+            run_hooks: false,
+            accepted_licenses: vec![],
+            force: false,
+            snapshot_tools: false,
+            journal: None,
         })
     }
     /// Creating install diretory and other preperations related to filesystem.
@@ -115,6 +141,7 @@ impl<'a> InstallConfiguration<'a> {
 
         // Create a copy of the manifest which is later used for component management.
         let manifest_out_path = install_dir.join(ToolsetManifest::FILENAME);
+        self.journal_created(&manifest_out_path)?;
         utils::write_file(manifest_out_path, &manifest.to_toml()?, false)?;
 
         // Create a copy of this binary
@@ -124,12 +151,19 @@ impl<'a> InstallConfiguration<'a> {
 
         // Add this manager to the `PATH` environment
         let manager_exe = install_dir.join(utils::exe!(manager_name));
+        self.journal_created(&manager_exe)?;
         utils::copy_as(self_exe, &manager_exe)?;
+        self.journal(Step::AddedToPath {
+            path: install_dir.clone(),
+        })?;
         add_to_path(install_dir)?;
 
         #[cfg(windows)]
-        // Create registry entry to add this program into "installed programs".
-        super::os::windows::do_add_to_programs(&manager_exe)?;
+        {
+            // Create registry entry to add this program into "installed programs".
+            self.journal(Step::AddedToPrograms)?;
+            super::os::windows::do_add_to_programs(&manager_exe)?;
+        }
 
         if let Some(prog) = &self.progress_indicator {
             prog.inc(Some(5.0))?;
@@ -139,19 +173,163 @@ impl<'a> InstallConfiguration<'a> {
     }
 
     pub fn install(mut self, components: Vec<Component>) -> Result<()> {
-        let (tc_components, tools) = split_components(components);
+        self.transaction(OperationKind::Install, components, Self::install_)
+    }
+
+    fn install_(&mut self, components: Vec<Component>) -> Result<()> {
+        let (tc_components, targets, tools) = split_components(components)?;
+        self.preflight(&tools, self.install_record.rust.is_none())?;
+        self.accept_licenses(&tools)?;
 
         self.setup()?;
+        self.journal(Step::ConfiguredEnv)?;
         self.config_env_vars()?;
         self.config_cargo()?;
         // This step taking cares of requirements, such as `MSVC`, also third-party app such as `VS Code`.
         self.install_tools(&tools)?;
-        self.install_rust(&tc_components)?;
+        self.install_rust(&tc_components, &targets)?;
         // install third-party tools via cargo that got installed by rustup
         self.cargo_install(&tools)?;
         Ok(())
     }
 
+    /// Get the licenses of the given components that need to be accepted by the user,
+    /// licenses that were accepted before are excluded.
+    pub fn licenses_to_accept<'c>(
+        &self,
+        components: &'c [Component],
+    ) -> Vec<(&'c str, &'c License)> {
+        components
+            .iter()
+            .filter_map(|c| Some((c.name.as_str(), c.tool_installer.as_ref()?.license()?)))
+            .filter(|(name, license)| {
+                !self
+                    .install_record
+                    .has_accepted_license(name, &license.name)
+            })
+            .collect()
+    }
+
+    /// Run an operation as a transaction, which records every change made by `op`
+    /// into a [`Journal`], and undo them if `op` fails or gets interrupted.
+    fn transaction<F>(
+        &mut self,
+        kind: OperationKind,
+        components: Vec<Component>,
+        op: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&mut Self, Vec<Component>) -> Result<()>,
+    {
+        let operation = Operation {
+            kind,
+            components: components.iter().map(|c| c.name.clone()).collect(),
+            cargo_registry: self.cargo_registry.clone(),
+            rustup_dist_server: self.rustup_dist_server.clone(),
+            rustup_update_root: self.rustup_update_root.clone(),
+            accepted_licenses: self.accepted_licenses.clone(),
+            run_hooks: self.run_hooks,
+        };
+        let history = HistoryTracker::start(kind.into(), &self.install_record);
+        let journal = Journal::begin(&self.install_dir, operation)?;
+        if kind == OperationKind::Update {
+            journal.keep_manifest(self.manifest)?;
+        }
+        self.journal = Some(journal);
+        let result = op(self, components);
+        let Some(journal) = self.journal.take() else {
+            return result;
+        };
+        match result {
+            Ok(()) => {
+                history.finish(&self.install_record, &Ok(()));
+                journal.finish()
+            }
+            Err(e) => {
+                // The record needs to be written before rolling back,
+                // because the rollback updates the record on disk.
+                if let Err(write_err) = self.install_record.write() {
+                    warn!("{write_err:?}");
+                }
+                if let Err(rollback_err) = journal.rollback() {
+                    warn!("{}", t!("rollback_failed", reason = rollback_err));
+                }
+                let result = Err(e);
+                // Nothing is left to keep the history for if the installation was rolled back entirely.
+                if self
+                    .install_dir
+                    .join(InstallationRecord::FILENAME)
+                    .is_file()
+                {
+                    match InstallationRecord::load(&self.install_dir) {
+                        Ok(record) => history.finish(&record, &result),
+                        Err(_) => history.finish(&self.install_record, &result),
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    /// Record a `step` into the journal of current operation, if there is one.
+    pub(crate) fn journal(&self, step: Step) -> Result<()> {
+        match &self.journal {
+            Some(journal) => journal.record(step),
+            None => Ok(()),
+        }
+    }
+
+    /// Record `path` as created by current operation if it does not exist yet,
+    /// which should be called right before creating it.
+    fn journal_created(&self, path: &Path) -> Result<()> {
+        if path.exists() {
+            return Ok(());
+        }
+        self.journal(Step::Created {
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Check the installation path and the free space needed for installing `tools`,
+    /// and a Rust toolchain if `with_toolchain`, before changing anything.
+    fn preflight(&self, tools: &ToolMap, with_toolchain: bool) -> Result<()> {
+        let mut issues = preflight::check_path(&self.install_dir);
+        let required = preflight::estimate_size(
+            tools,
+            with_toolchain,
+            Some(preflight::RemoteSize {
+                proxy: self.manifest.proxy.as_ref(),
+                insecure: self.insecure,
+            }),
+        );
+        issues.extend(preflight::check_space(&self.install_dir, required));
+        preflight::ensure_passed(&issues)
+    }
+
+    /// Make sure the licenses of every tool were accepted, and record the acceptances.
+    fn accept_licenses(&mut self, tools: &ToolMap) -> Result<()> {
+        for (name, tool) in tools {
+            let Some(license) = tool.license() else {
+                continue;
+            };
+            if self
+                .install_record
+                .has_accepted_license(name, &license.name)
+            {
+                continue;
+            }
+            if !self.accepted_licenses.contains(name) {
+                bail!(
+                    "the license '{}' of '{name}' must be accepted before installing it, \
+                    use `--accept-license {name}` to accept it",
+                    license.name
+                );
+            }
+            self.install_record.accept_license(name, &license.name);
+        }
+        Ok(())
+    }
+
     pub(crate) fn inc_progress(&self, val: f32) -> Result<()> {
         if let Some(prog) = &self.progress_indicator {
             prog.inc(Some(val))?;
@@ -168,6 +346,9 @@ impl<'a> InstallConfiguration<'a> {
     setter!(rustup_update_root(self, Url));
     setter!(progress_indicator(self, Option<Progress<'a>>));
     setter!(insecure(self, bool));
+    setter!(run_hooks(self, bool));
+    setter!(accepted_licenses(self, Vec<String>));
+    setter!(snapshot_tools(self, bool));
 
     pub(crate) fn env_vars(&self) -> Result<HashMap<&'static str, String>> {
         let cargo_home = self
@@ -202,16 +383,13 @@ impl<'a> InstallConfiguration<'a> {
         Ok(env_vars)
     }
 
-    fn install_tools_(&mut self, use_cargo: bool, tools: &ToolMap, weight: f32) -> Result<()> {
+    /// Install the `tools` that could be installed before the rust toolchain,
+    /// or the ones that require it (check [`tools_requiring_rust`]) if `after_rust` is true.
+    fn install_tools_(&mut self, after_rust: bool, tools: &ToolMap, weight: f32) -> Result<()> {
+        let requiring_rust = tools_requiring_rust(tools);
         let to_install = tools
             .into_iter()
-            .filter(|(_, t)| {
-                if use_cargo {
-                    t.is_cargo_tool()
-                } else {
-                    !t.is_cargo_tool()
-                }
-            })
+            .filter(|(name, _)| requiring_rust.contains(name.as_str()) == after_rust)
             .collect::<Vec<_>>();
 
         if to_install.is_empty() {
@@ -220,7 +398,7 @@ impl<'a> InstallConfiguration<'a> {
         let sub_progress_delta = weight / to_install.len() as f32;
 
         for (name, tool) in to_install {
-            let info = if use_cargo {
+            let info = if tool.is_cargo_tool() {
                 t!("installing_via_cargo_info", name = name)
             } else {
                 t!("installing_tool_info", name = name)
@@ -247,22 +425,34 @@ impl<'a> InstallConfiguration<'a> {
         self.install_tools_(true, tools, 30.0)
     }
 
-    pub fn install_rust(&mut self, optional_components: &[String]) -> Result<()> {
+    pub fn install_rust(
+        &mut self,
+        optional_components: &[String],
+        optional_targets: &[String],
+    ) -> Result<()> {
         info!("{}", t!("install_toolchain"));
 
         let manifest = self.manifest;
+        let targets = merge_names(&manifest.rust.targets, optional_targets);
 
+        if self.install_record.rust.is_none() {
+            self.journal(Step::InstalledToolchain)?;
+        }
         ToolchainInstaller::init().insecure(self.insecure).install(
             self,
             manifest,
             optional_components,
+            &targets,
         )?;
+        self.journal(Step::AddedToPath {
+            path: self.cargo_bin().to_path_buf(),
+        })?;
         add_to_path(self.cargo_bin())?;
         self.cargo_is_installed = true;
 
         // Add the rust info to the fingerprint.
         self.install_record
-            .add_rust_record(manifest.rust_version(), optional_components);
+            .add_rust_record(manifest.rust_version(), optional_components, &targets);
         // record meta info
         // TODO(?): Maybe this should be moved as a separate step?
         self.install_record
@@ -276,17 +466,26 @@ impl<'a> InstallConfiguration<'a> {
     // TODO: Write version info after installing each tool,
     // which is later used for updating.
     fn install_tool(&mut self, name: &str, tool: &ToolInfo) -> Result<()> {
+        let is_new = !self.install_record.tools.contains_key(name);
+        if !tool.is_cargo_tool() {
+            self.journal_created(&self.tools_dir().join(name))?;
+        }
         let tool_ver = tool.version();
         let record = match tool {
             ToolInfo::PlainVersion(version) | ToolInfo::DetailedVersion { ver: version, .. } => {
-                Tool::cargo_tool(name, Some(vec![name, "--version", version]))
-                    .install(tool_ver, self)?
+                let version = self.resolve_cargo_tool_version(name, version)?;
+                let mut args = vec![name, "--version", &version];
+                if let Some(options) = tool.cargo_options() {
+                    args.extend(options.args());
+                }
+                self.install_cargo_tool(name, Some(&version), tool, args)?
             }
             ToolInfo::Git {
                 git,
                 branch,
                 tag,
                 rev,
+                cargo,
                 ..
             } => {
                 let mut args = vec!["--git", git.as_str()];
@@ -299,33 +498,236 @@ impl<'a> InstallConfiguration<'a> {
                 if let Some(s) = &rev {
                     args.extend(["--rev", s]);
                 }
+                args.extend(cargo.args());
 
-                Tool::cargo_tool(name, Some(args)).install(tool_ver, self)?
+                let record = self.install_cargo_tool(name, tool_ver, tool, args)?;
+                // branches and tags could move, remember the exact commit that was built
+                let rev = installed_files::cargo_tool_git_rev(self.cargo_home(), name)?;
+                record.rev(rev)
+            }
+            ToolInfo::Path { path, .. } => {
+                verify_package(name, tool, path)?;
+                self.try_install_from_path(name, tool_ver, path)?
             }
-            ToolInfo::Path { path, .. } => self.try_install_from_path(name, tool_ver, path)?,
             // TODO: Have a dedicated download folder, do not use temp dir to store downloaded artifacts,
             // so then we can have the `resume download` feature.
             ToolInfo::Url { url, .. } => {
                 let temp_dir = self.create_temp_dir("download")?;
-                let downloaded_file_name = url
-                    .path_segments()
-                    .ok_or_else(|| anyhow!("unsupported url format '{url}'"))?
-                    .last()
-                    // Sadly, a path segment could be empty string, so we need to filter that out
-                    .filter(|seg| !seg.is_empty())
-                    .ok_or_else(|| anyhow!("'{url}' doesn't appear to be a downloadable file"))?;
-                let dest = temp_dir.path().join(downloaded_file_name);
-                utils::download_with_proxy(name, url, &dest, self.manifest.proxy.as_ref())?;
+                let (mirror, dest) = self.download_tool(name, tool, url.urls(), temp_dir.path())?;
 
+                let sha256 = utils::file_digest(&dest, HashAlgorithm::Sha256)?;
                 self.try_install_from_path(name, tool_ver, &dest)?
+                    .url(Some(mirror.clone()))
+                    .sha256(Some(sha256))
             }
         };
 
-        self.install_record.add_tool_record(name, record);
+        let vars = self.tool_variables(name)?;
+        let env = tool
+            .env_vars()
+            .map(|(key, val)| Ok((key.to_string(), vars.expand(val)?)))
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        if is_new {
+            self.journal(Step::InstalledTool {
+                name: name.to_string(),
+                record: record.clone(),
+            })?;
+        }
+        // a tool being updated might have set some of these variables already
+        let previous = self
+            .install_record
+            .tools
+            .get(name)
+            .map(|old| {
+                old.env
+                    .iter()
+                    .filter(|(key, _)| env.contains_key(*key))
+                    .map(|(key, val)| (key.clone(), val.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.journal(Step::SetEnvVars {
+            keys: env.keys().cloned().collect(),
+            previous,
+        })?;
+        set_env_vars(&env)?;
+
+        let hooks = tool
+            .hooks()
+            .map(|hooks| hooks.expand(&vars))
+            .transpose()?
+            .unwrap_or_default();
+        if self.run_hooks {
+            run_hooks(name, "post-install", &hooks.post_install, self)?;
+        } else if !hooks.post_install.is_empty() {
+            warn!(
+                "{}",
+                t!("hooks_skipped", name = name, stage = "post-install")
+            );
+        }
+
+        self.record_files(name, &record)?;
+        self.install_record.add_tool_record(
+            name,
+            record
+                .dependencies(tool.dependencies().to_vec())
+                .env(env)
+                .hooks(hooks),
+        );
 
         Ok(())
     }
 
+    /// Record every file installed for tool `name`, which is used for verifying the installation.
+    fn record_files(&self, name: &str, record: &ToolRecord) -> Result<()> {
+        let paths = match record.tool_kind() {
+            ToolKind::CargoTool => installed_files::cargo_tool_bins(self.cargo_home(), name)?,
+            _ => record.paths.clone(),
+        };
+        let mut files = InstalledFiles::load_from_root(&self.install_dir)?;
+        files.add_tool(&self.install_dir, name, &paths)?;
+        files.write(&self.install_dir)
+    }
+
+    /// Get the variables that can be used in the environment variables and hooks of a tool,
+    /// which are `${install_dir}` and `${tool_dir}`.
+    fn tool_variables(&self, name: &str) -> Result<Variables> {
+        Ok(Variables::default()
+            .with("install_dir", utils::path_to_str(&self.install_dir)?)
+            .with(
+                "tool_dir",
+                utils::path_to_str(&self.tools_dir().join(name))?,
+            ))
+    }
+
+    /// Download the package of a tool into `dir` by trying each of its mirrors in order,
+    /// until one of them was downloaded and verified successfully.
+    ///
+    /// Return the url of the mirror used, along with the path to the downloaded package.
+    fn download_tool<'u>(
+        &self,
+        name: &str,
+        tool: &ToolInfo,
+        urls: &'u [Url],
+        dir: &Path,
+    ) -> Result<(&'u Url, PathBuf)> {
+        let mut last_err = None;
+        for url in urls {
+            let res = downloaded_file_name(url).and_then(|file_name| {
+                let dest = dir.join(file_name);
+                utils::download_with_proxy(name, url, &dest, self.manifest.proxy.as_ref())?;
+                verify_package(name, tool, &dest)?;
+                Ok(dest)
+            });
+            match res {
+                Ok(dest) => return Ok((url, dest)),
+                Err(e) => {
+                    warn!("{}", t!("mirror_download_failed", url = url, reason = e));
+                    last_err = Some(e);
+                }
+            }
+        }
+        let err = last_err.unwrap_or_else(|| anyhow!("no url was provided"));
+        Err(err.context(format!("unable to download '{name}' from any of its urls")))
+    }
+
+    /// Install a tool that is managed by `cargo`, by installing its prebuilt binaries
+    /// if there are any, or with `cargo install` otherwise.
+    fn install_cargo_tool(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        tool: &ToolInfo,
+        args: Vec<&str>,
+    ) -> Result<ToolRecord> {
+        if let Some(options) = tool.cargo_options() {
+            if let Some(prebuilt) = &options.prebuilt {
+                // `${version}` is kept in the urls if the tool has a version requirement
+                let prebuilt = match version {
+                    Some(ver) => prebuilt.with_version(ver)?,
+                    None => prebuilt.clone(),
+                };
+                match self.install_prebuilt(name, version, tool, prebuilt.urls(), &options.bins) {
+                    Ok(record) => return Ok(record),
+                    Err(e) => warn!("{}", t!("prebuilt_install_failed", name = name, reason = e)),
+                }
+            }
+        }
+        let mut args = args;
+        if self.force {
+            args.push("--force");
+        }
+        Tool::cargo_tool(name, Some(args)).install(version, self)
+    }
+
+    /// Resolve the version requirement (such as `^0.9`) of a cargo tool into the newest
+    /// matching version in the cargo registry, exact versions are returned as is.
+    pub(crate) fn resolve_cargo_tool_version(&self, name: &str, version: &str) -> Result<String> {
+        let Some(req) = registry::version_req(version) else {
+            return Ok(version.to_string());
+        };
+        let registry = self
+            .cargo_registry
+            .as_ref()
+            .map_or(DEFAULT_CARGO_REGISTRY.1, |(_, url)| url.as_str());
+        let resolved =
+            registry::resolve_version(registry, name, &req, self.manifest.proxy.as_ref())?;
+        info!(
+            "{}",
+            t!(
+                "version_resolved",
+                name = name,
+                req = version,
+                version = resolved
+            )
+        );
+        Ok(resolved.to_string())
+    }
+
+    /// Download the prebuilt archive of a cargo tool, then put its binaries into `cargo_bin`.
+    ///
+    /// Only the binaries named in `bins` (or the tool name if it's empty) will be installed.
+    fn install_prebuilt(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        tool: &ToolInfo,
+        urls: &[Url],
+        bins: &[String],
+    ) -> Result<ToolRecord> {
+        let temp_dir = self.create_temp_dir("download")?;
+        let (_, archive) = self.download_tool(name, tool, urls, temp_dir.path())?;
+        let extracted = self.create_temp_dir(name)?;
+        let mut extractable = Extractable::load(&archive)?;
+        extractable.extract_to(extracted.path())?;
+
+        let wanted = if bins.is_empty() {
+            vec![name]
+        } else {
+            bins.iter().map(String::as_str).collect()
+        };
+        let binaries = utils::walk_dir(extracted.path(), true)?
+            .into_iter()
+            .filter(|path| {
+                utils::is_executable(path)
+                    && path
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .is_some_and(|stem| wanted.contains(&stem))
+            })
+            .collect::<Vec<_>>();
+        if binaries.is_empty() {
+            bail!(
+                "no binary named '{}' was found in the prebuilt archive",
+                wanted.join("', '")
+            );
+        }
+
+        Tool::new(name.to_string(), ToolKind::Executables)
+            .path(binaries)
+            .install(version, self)
+    }
+
     fn try_install_from_path(
         &self,
         name: &str,
@@ -360,6 +762,7 @@ impl<'a> InstallConfiguration<'a> {
         let config_toml = config.to_toml()?;
         if !config_toml.trim().is_empty() {
             let config_path = self.cargo_home().join(CargoConfig::FILENAME);
+            self.journal_created(&config_path)?;
             utils::write_file(config_path, &config_toml, false)?;
         }
 
@@ -392,30 +795,93 @@ impl<'a> InstallConfiguration<'a> {
 
 // For updates
 impl InstallConfiguration<'_> {
+    /// Update the installed toolkit to the one of this configuration's manifest,
+    /// a snapshot of the installed toolkit is taken beforehand so it could be rolled back.
     pub fn update(mut self, components: Vec<Component>) -> Result<()> {
-        let (_, tools) = split_components(components);
+        let replaced: Vec<&str> = if self.snapshot_tools {
+            components
+                .iter()
+                .filter(|c| self.install_record.tools.contains_key(&c.name))
+                .map(|c| c.name.as_str())
+                .collect()
+        } else {
+            vec![]
+        };
+        // an interrupted update that is being completed should keep the snapshot
+        // of the toolkit before it, instead of taking one of a half-updated toolkit
+        if !self.install_dir.join(Journal::FILENAME).is_file() {
+            Snapshot::take(&self.install_record, &replaced)?;
+        }
+
+        self.transaction(OperationKind::Update, components, |config, components| {
+            config.update_(components)?;
+            config.write_manifest()
+        })
+    }
+
+    /// Install more `components` of the installed toolkit.
+    pub fn install_components(mut self, components: Vec<Component>) -> Result<()> {
+        check_conflicts_with_installed(&components, self.install_record.installed_tools())?;
+        self.transaction(OperationKind::InstallComponents, components, Self::update_)
+    }
+
+    fn update_(&mut self, components: Vec<Component>) -> Result<()> {
+        let (tc_components, targets, tools) = split_components(components)?;
+        self.preflight(&tools, false)?;
+        self.accept_licenses(&tools)?;
         // setup env for current process
         for (key, val) in self.env_vars()? {
             std::env::set_var(key, val);
         }
         self.inc_progress(10.0)?;
 
-        self.update_toolchain()?;
+        self.update_toolchain(&tc_components, &targets)?;
         self.update_tools(&tools)?;
         Ok(())
     }
 
-    fn update_toolchain(&mut self) -> Result<()> {
+    /// Replace the installed manifest with the one of this configuration,
+    /// which is later used for component management.
+    fn write_manifest(&self) -> Result<()> {
+        let manifest_out_path = self.install_dir.join(ToolsetManifest::FILENAME);
+        utils::write_file(manifest_out_path, &self.manifest.to_toml()?, false)
+    }
+
+    fn update_toolchain(
+        &mut self,
+        optional_components: &[String],
+        optional_targets: &[String],
+    ) -> Result<()> {
         info!("{}", t!("update_toolchain"));
 
         let manifest = self.manifest;
+        // keep the components and targets that were installed previously
+        let installed_components = self
+            .install_record
+            .installed_toolchain()
+            .map(|(_, components)| components.map(ToOwned::to_owned).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let components = merge_names(&installed_components, optional_components);
+        let installed_targets = self
+            .install_record
+            .installed_targets()
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        let targets = merge_names(
+            &merge_names(&installed_targets, &manifest.rust.targets),
+            optional_targets,
+        );
 
-        ToolchainInstaller::init()
-            .insecure(self.insecure)
-            .update(self, manifest)?;
+        ToolchainInstaller::init().insecure(self.insecure).update(
+            self,
+            manifest,
+            &components,
+            &targets,
+        )?;
 
         // Add the rust info to the fingerprint.
-        self.install_record.update_rust(manifest.rust_version());
+        self.install_record
+            .update_rust(manifest.rust_version(), &components, &targets);
         // record meta info
         self.install_record
             .clone_toolkit_meta_from_manifest(manifest);
@@ -431,6 +897,131 @@ impl InstallConfiguration<'_> {
         self.install_tools_(true, tools, 15.0)?;
         Ok(())
     }
+
+    /// Reinstall the given (installed) components, overwriting their files.
+    pub fn repair(mut self, components: Vec<Component>) -> Result<()> {
+        self.transaction(OperationKind::Repair, components, Self::repair_)
+    }
+
+    fn repair_(&mut self, components: Vec<Component>) -> Result<()> {
+        let (_, _, tools) = split_components(components)?;
+        self.accept_licenses(&tools)?;
+        for (key, val) in self.env_vars()? {
+            std::env::set_var(key, val);
+        }
+        self.cargo_is_installed = self.install_record.rust.is_some();
+        self.force = true;
+        self.inc_progress(10.0)?;
+
+        info!("{}", t!("repairing_tools"));
+        self.install_tools_(false, &tools, 45.0)?;
+        self.install_tools_(true, &tools, 45.0)
+    }
+
+    /// Roll the installed toolkit back to the one in `snapshot`, which is the toolkit
+    /// installed before the last update.
+    ///
+    /// Note that the manifest of this configuration should be the one in the snapshot.
+    pub fn rollback(mut self, snapshot: Snapshot) -> Result<()> {
+        self.transaction(OperationKind::Rollback, vec![], |config, _| {
+            config.rollback_(&snapshot)
+        })?;
+        snapshot.remove()
+    }
+
+    fn rollback_(&mut self, snapshot: &Snapshot) -> Result<()> {
+        let previous = snapshot.record()?;
+        for (key, val) in self.env_vars()? {
+            std::env::set_var(key, val);
+        }
+        self.cargo_is_installed = self.install_record.rust.is_some();
+        self.force = true;
+        self.inc_progress(10.0)?;
+
+        // restore the toolchain
+        let current_tc = self
+            .install_record
+            .installed_toolchain()
+            .map(|(ver, _)| ver);
+        if let (Some(current), Some((previous_tc, _))) =
+            (current_tc, previous.installed_toolchain())
+        {
+            if current != previous_tc {
+                info!("{}", t!("rolling_back_toolchain", version = previous_tc));
+                let current = current.to_string();
+                let components: Vec<String> = previous
+                    .installed_toolchain()
+                    .map(|(_, components)| components.map(ToOwned::to_owned).collect())
+                    .unwrap_or_default();
+                let targets: Vec<String> = previous
+                    .installed_targets()
+                    .map(ToOwned::to_owned)
+                    .collect();
+                ToolchainInstaller::init()
+                    .insecure(self.insecure)
+                    .rollback(self, self.manifest, &components, &targets, &current)?;
+            }
+        }
+        self.inc_progress(30.0)?;
+
+        // remove the tools that were added by the update
+        let added: IndexMap<String, ToolRecord> = self
+            .install_record
+            .tools
+            .iter()
+            .filter(|(name, _)| !previous.tools.contains_key(*name))
+            .map(|(name, record)| (name.clone(), record.clone()))
+            .collect();
+        if !added.is_empty() {
+            self.install_record.write()?;
+            let mut uninstall = UninstallConfiguration::init_in(&self.install_dir)?;
+            uninstall.remove_tools(added, 0.0)?;
+            self.install_record = uninstall.install_record;
+        }
+
+        // restore the tools that were replaced or removed by the update, using the copies
+        // of their files if there are any, otherwise reinstall them
+        let mut to_reinstall = vec![];
+        for (name, record) in &previous.tools {
+            let version = self.install_record.get_tool_version(name);
+            if self.install_record.tools.contains_key(name)
+                && version == previous.get_tool_version(name)
+            {
+                continue;
+            }
+            match snapshot.tool_files(name, &record.paths) {
+                Some(files) => {
+                    info!("{}", t!("restoring_tool", name = name));
+                    for (copy, dest) in files {
+                        utils::remove(&dest)?;
+                        utils::copy_as(copy, &dest)?;
+                    }
+                    self.install_record.add_tool_record(name, record.clone());
+                    self.record_files(name, record)?;
+                }
+                None => to_reinstall.push(name.as_str()),
+            }
+        }
+        let tools: ToolMap = self
+            .manifest
+            .current_target_tools()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(name, _)| to_reinstall.contains(&name.as_str()))
+            .collect();
+        let tools = sort_by_dependencies(&tools)?;
+        self.install_tools_(false, &tools, 25.0)?;
+        self.install_tools_(true, &tools, 25.0)?;
+
+        // restore the record and the manifest
+        self.install_record.rust = previous.rust;
+        self.install_record
+            .clone_toolkit_meta_from_manifest(self.manifest);
+        self.install_record.write()?;
+        self.write_manifest()?;
+
+        self.inc_progress(10.0)
+    }
 }
 
 /// Get the default installation directory,
@@ -439,31 +1030,67 @@ pub fn default_install_dir() -> PathBuf {
     utils::home_dir().join(&*t!("vendor_en"))
 }
 
-/// Split components list to `toolchain_components` and `toolset_components`,
-/// as we are running `rustup` to install toolchain components, but using other methods
-/// for toolset components.
-fn split_components(components: Vec<Component>) -> (Vec<String>, ToolMap) {
-    let toolset_components = component_list_to_tool_map(
+/// Get the name of the file that `url` points to.
+fn downloaded_file_name(url: &Url) -> Result<&str> {
+    url.path_segments()
+        .ok_or_else(|| anyhow!("unsupported url format '{url}'"))?
+        .last()
+        // Sadly, a path segment could be empty string, so we need to filter that out
+        .filter(|seg| !seg.is_empty())
+        .ok_or_else(|| anyhow!("'{url}' doesn't appear to be a downloadable file"))
+}
+
+/// Verify the package of a tool against the checksums specified in the manifest, if there are any.
+///
+/// This should be called before extracting or copying `package`, so that a corrupted package
+/// can be caught before anything is being installed.
+fn verify_package(name: &str, tool: &ToolInfo, package: &Path) -> Result<()> {
+    for (algorithm, expected) in tool.checksums() {
+        debug!("verifying {algorithm} checksum of '{}'", package.display());
+        utils::verify_checksum(name, package, algorithm, expected)?;
+    }
+    Ok(())
+}
+
+/// Combine two lists of names without duplicates.
+fn merge_names(a: &[String], b: &[String]) -> Vec<String> {
+    let mut names = a.to_vec();
+    for name in b {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+    names
+}
+
+/// Split a list of components into toolchain components, toolchain targets, and tools.
+///
+/// The tools are sorted by their dependencies, so that every tool can be installed
+/// after the ones it depends on.
+///
+/// # Errors
+/// Return `Err` if some of the components cannot be installed together,
+/// or there are circular dependencies.
+fn split_components(components: Vec<Component>) -> Result<(Vec<String>, Vec<String>, ToolMap)> {
+    check_conflicts(&components)?;
+    let toolset_components = sort_by_dependencies(&component_list_to_tool_map(
         components
             .iter()
             .filter(|cm| !cm.is_toolchain_component)
             .collect(),
-    );
-    let toolchain_components: Vec<String> = components
+    ))?;
+    let (targets, toolchain_components): (Vec<Component>, Vec<Component>) = components
         .into_iter()
-        // Skip the mocked `rust toolchain` component that we added first,
+        // Skip the mocked `rust toolchain` component,
         // it will be installed as requirement anyway.
-        .skip(1)
-        .filter_map(|comp| {
-            if comp.is_toolchain_component {
-                Some(comp.name)
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    (toolchain_components, toolset_components)
+        .filter(|comp| comp.is_toolchain_component && !comp.is_rust_toolchain())
+        .partition(|comp| comp.is_target);
+
+    Ok((
+        toolchain_components.into_iter().map(|c| c.name).collect(),
+        targets.into_iter().map(|c| c.name).collect(),
+        toolset_components,
+    ))
 }
 
 #[cfg(test)]
@@ -486,6 +1113,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn split_selected_components() {
+        let manifest = ToolsetManifest::from_str(
+            r#"
+[rust]
+version = "1.80.0"
+optional-components = ["llvm-tools"]
+optional-targets = ["aarch64-unknown-linux-gnu", "wasm32-unknown-unknown"]
+"#,
+        )
+        .unwrap();
+        let all = manifest.current_target_components(false).unwrap();
+        let select = |names: &[&str]| -> Vec<Component> {
+            all.iter()
+                .filter(|c| names.contains(&c.name.as_str()))
+                .cloned()
+                .collect()
+        };
+
+        // only one optional target, such as the ones selected by `component install`
+        let (components, targets, tools) =
+            split_components(select(&["wasm32-unknown-unknown"])).unwrap();
+        assert!(components.is_empty());
+        assert_eq!(targets, ["wasm32-unknown-unknown"]);
+        assert!(tools.is_empty());
+
+        let mut selected = vec![all[0].clone()];
+        selected.extend(select(&["llvm-tools", "aarch64-unknown-linux-gnu"]));
+        let (components, targets, _) = split_components(selected).unwrap();
+        assert_eq!(components, ["llvm-tools"]);
+        assert_eq!(targets, ["aarch64-unknown-linux-gnu"]);
+    }
+
     #[test]
     fn init_install_config() {
         let mut cache_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -0,0 +1,370 @@
+//! A journal of the changes made by an installation, so that an operation that failed or
+//! was interrupted halfway could be rolled back, instead of leaving the machine half-configured.
+//!
+//! Each change is appended as one line of JSON into a file under the installation directory
+//! as soon as it was made, and the file is removed once the operation was completed.
+//!
+//! Note that the files of an existing tool overwritten by an update are not backed up,
+//! so undoing an update leaves them as they are,
+//! restoring them is done by [`Snapshot`](super::snapshot::Snapshot) instead.
+
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::os::{remove_env_vars, remove_from_path, set_env_vars};
+use super::parser::fingerprint::ToolRecord;
+use super::parser::toolset_manifest::ToolsetManifest;
+use super::parser::TomlParser;
+use super::rustup::ToolchainInstaller;
+use super::uninstall::{UninstallConfiguration, Uninstallation};
+use crate::utils;
+
+/// Whether an operation is in progress, interrupting the program (with `Ctrl-C`)
+/// stops the operation instead of exiting immediately.
+static IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// The kind of operations that could be journaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OperationKind {
+    Install,
+    Update,
+    InstallComponents,
+    Repair,
+    Rollback,
+}
+
+/// Everything needed to run an operation again, so it could be completed after an interruption.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Operation {
+    pub kind: OperationKind,
+    /// Names of the components to install.
+    pub components: Vec<String>,
+    pub cargo_registry: Option<(String, String)>,
+    pub rustup_dist_server: Url,
+    pub rustup_update_root: Url,
+    pub accepted_licenses: Vec<String>,
+    pub run_hooks: bool,
+}
+
+/// A change made by an operation, which knows how to be undone.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "step", rename_all = "kebab-case")]
+pub(crate) enum Step {
+    /// The start of an operation, a journal might have multiple of them
+    /// if an interrupted operation was run again.
+    Begin {
+        operation: Operation,
+    },
+    /// A file or directory that didn't exist before was created.
+    Created {
+        path: PathBuf,
+    },
+    AddedToPath {
+        path: PathBuf,
+    },
+    /// The environment variables of `rustup` (such as `CARGO_HOME`) were configured.
+    ConfiguredEnv,
+    /// The environment variables of a tool were set, `previous` contains the values of the ones
+    /// that were already set by the tool before, which are restored when undone.
+    SetEnvVars {
+        keys: Vec<String>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        previous: BTreeMap<String, String>,
+    },
+    /// This program was added to the installed programs on Windows.
+    AddedToPrograms,
+    InstalledToolchain,
+    /// A tool that wasn't installed before was installed.
+    InstalledTool {
+        name: String,
+        record: ToolRecord,
+    },
+}
+
+pub(crate) struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub(crate) const FILENAME: &str = ".journal";
+    /// The file keeping the manifest that an update is installing,
+    /// as the installed manifest is only replaced once the update was completed.
+    const MANIFEST_FILENAME: &str = ".journal-manifest.toml";
+
+    /// Start journaling an `operation` in `root`.
+    ///
+    /// If there's an unfinished operation, its journal will be continued,
+    /// so that a failure of this operation undoes the changes of both.
+    pub(crate) fn begin(root: &Path, operation: Operation) -> Result<Self> {
+        static HANDLER: Once = Once::new();
+        HANDLER.call_once(|| {
+            let res = ctrlc::set_handler(|| {
+                if IN_PROGRESS.load(Ordering::SeqCst) {
+                    warn!("{}", t!("operation_interrupted"));
+                    INTERRUPTED.store(true, Ordering::SeqCst);
+                } else {
+                    std::process::exit(130);
+                }
+            });
+            if let Err(e) = res {
+                warn!("unable to handle interruptions: {e}");
+            }
+        });
+
+        let journal = Self {
+            path: root.join(Self::FILENAME),
+        };
+        if journal.path.is_file() {
+            warn!("{}", t!("continuing_unfinished_operation"));
+        }
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        IN_PROGRESS.store(true, Ordering::SeqCst);
+        journal.record(Step::Begin { operation })?;
+        Ok(journal)
+    }
+
+    /// Append a `step` to the journal, which should be done right after the change was made.
+    ///
+    /// # Error
+    /// Besides IO errors, this also fails if the program was interrupted,
+    /// which stops the operation so that it could be rolled back.
+    pub(crate) fn record(&self, step: Step) -> Result<()> {
+        let line = serde_json::to_string(&step)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("unable to open journal '{}'", self.path.display()))?;
+        writeln!(file, "{line}")?;
+        file.sync_data()?;
+
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            bail!(t!("operation_interrupted"));
+        }
+        Ok(())
+    }
+
+    /// Keep a copy of the `manifest` that the operation installs from, so that the
+    /// operation could be completed with the same manifest after an interruption.
+    pub(crate) fn keep_manifest(&self, manifest: &ToolsetManifest) -> Result<()> {
+        let root = self.path.parent().unwrap_or(Path::new("."));
+        utils::write_file(
+            root.join(Self::MANIFEST_FILENAME),
+            &manifest.to_toml()?,
+            false,
+        )
+    }
+
+    /// Mark the operation as completed, which removes the journal.
+    pub(crate) fn finish(self) -> Result<()> {
+        IN_PROGRESS.store(false, Ordering::SeqCst);
+        let root = self.path.parent().unwrap_or(Path::new("."));
+        utils::remove(root.join(Self::MANIFEST_FILENAME))?;
+        utils::remove(&self.path)
+    }
+
+    /// Undo every change recorded in the journal, then remove the journal.
+    pub(crate) fn rollback(self) -> Result<()> {
+        IN_PROGRESS.store(false, Ordering::SeqCst);
+        let root = self.path.parent().unwrap_or(Path::new("."));
+        rollback(root)
+    }
+}
+
+fn read_steps(root: &Path) -> Result<Option<Vec<Step>>> {
+    let path = root.join(Journal::FILENAME);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = utils::read_to_string("journal", &path)?;
+    let steps = content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        // the last line might be incomplete if the program was killed while writing it
+        .map_while(|line| serde_json::from_str(line).ok())
+        .collect();
+    Ok(Some(steps))
+}
+
+/// Get the operation that was interrupted in `root`, if there is one.
+pub fn unfinished_operation(root: &Path) -> Result<Option<Operation>> {
+    let Some(steps) = read_steps(root)? else {
+        return Ok(None);
+    };
+    let last_op = steps.into_iter().rev().find_map(|step| match step {
+        Step::Begin { operation } => Some(operation),
+        _ => None,
+    });
+    Ok(last_op)
+}
+
+/// Get the manifest kept by the unfinished operation in `root`, if there is one.
+///
+/// Only updates keep their manifests, the other operations install from the installed manifest.
+pub fn unfinished_manifest(root: &Path) -> Result<Option<ToolsetManifest>> {
+    let path = root.join(Journal::MANIFEST_FILENAME);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    ToolsetManifest::load(path).map(Some)
+}
+
+/// Undo the changes made by the unfinished operation in `root`, then remove its journal.
+///
+/// This does nothing if there isn't any unfinished operation.
+pub fn rollback(root: &Path) -> Result<()> {
+    let Some(steps) = read_steps(root)? else {
+        return Ok(());
+    };
+    info!("{}", t!("rolling_back"));
+
+    let mut config = UninstallConfiguration::init_in(root)?;
+    for step in steps.into_iter().rev() {
+        if let Err(e) = undo(&mut config, step) {
+            warn!("{}", t!("rollback_step_failed", reason = e));
+        }
+    }
+    utils::remove(root.join(Journal::MANIFEST_FILENAME))?;
+    utils::remove(root.join(Journal::FILENAME))
+}
+
+fn undo(config: &mut UninstallConfiguration, step: Step) -> Result<()> {
+    match step {
+        Step::Begin { .. } => (),
+        Step::Created { path } => {
+            if path.exists() {
+                utils::remove(path)?;
+            }
+        }
+        Step::AddedToPath { path } => remove_from_path(&path)?,
+        Step::ConfiguredEnv => config.remove_rustup_env_vars()?,
+        Step::SetEnvVars { keys, previous } => {
+            remove_env_vars(&keys.iter().map(String::as_str).collect::<Vec<_>>())?;
+            set_env_vars(&previous)?;
+        }
+        Step::AddedToPrograms => {
+            #[cfg(windows)]
+            super::os::windows::do_remove_from_programs(super::os::windows::uninstall_entry())?;
+        }
+        Step::InstalledToolchain => {
+            ToolchainInstaller::init().remove_self(config)?;
+            config.install_record.remove_rust_record();
+            config.install_record.remove_toolkit_meta();
+            config.install_record.write()?;
+        }
+        Step::InstalledTool { name, record } => {
+            config.remove_tools(IndexMap::from([(name, record)]), 0.0)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operation() -> Operation {
+        Operation {
+            kind: OperationKind::Install,
+            components: vec!["aaa".into()],
+            cargo_registry: None,
+            rustup_dist_server: "https://static.rust-lang.org".parse().unwrap(),
+            rustup_update_root: "https://static.rust-lang.org/rustup".parse().unwrap(),
+            accepted_licenses: vec![],
+            run_hooks: false,
+        }
+    }
+
+    #[test]
+    fn journal_lifecycle() {
+        let root = tempfile::tempdir().unwrap();
+        let created = root.path().join("created");
+        utils::ensure_dir(&created).unwrap();
+
+        let journal = Journal::begin(root.path(), operation()).unwrap();
+        journal
+            .record(Step::Created {
+                path: created.clone(),
+            })
+            .unwrap();
+        let op = unfinished_operation(root.path()).unwrap().unwrap();
+        assert_eq!(op.kind, OperationKind::Install);
+        assert_eq!(op.components, ["aaa"]);
+        assert_eq!(read_steps(root.path()).unwrap().unwrap().len(), 2);
+
+        journal.finish().unwrap();
+        assert!(unfinished_operation(root.path()).unwrap().is_none());
+        assert!(created.exists());
+    }
+
+    #[test]
+    fn keep_manifest_of_update() {
+        let root = tempfile::tempdir().unwrap();
+        let manifest = ToolsetManifest::from_str("[rust]\nversion = \"1.81.0\"").unwrap();
+
+        let journal = Journal::begin(root.path(), operation()).unwrap();
+        assert!(unfinished_manifest(root.path()).unwrap().is_none());
+        journal.keep_manifest(&manifest).unwrap();
+        let kept = unfinished_manifest(root.path()).unwrap().unwrap();
+        assert_eq!(kept.rust_version(), "1.81.0");
+
+        journal.finish().unwrap();
+        assert!(unfinished_manifest(root.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn rollback_created_paths() {
+        let root = tempfile::tempdir().unwrap();
+        let existing = root.path().join("existing");
+        let created = root.path().join("created");
+        utils::ensure_dir(&existing).unwrap();
+
+        let journal = Journal::begin(root.path(), operation()).unwrap();
+        utils::ensure_dir(&created).unwrap();
+        journal
+            .record(Step::Created {
+                path: created.clone(),
+            })
+            .unwrap();
+        journal.rollback().unwrap();
+
+        assert!(!created.exists());
+        assert!(existing.exists());
+        assert!(unfinished_operation(root.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn incomplete_last_line() {
+        let root = tempfile::tempdir().unwrap();
+        let journal = Journal::begin(root.path(), operation()).unwrap();
+        journal.record(Step::ConfiguredEnv).unwrap();
+        utils::write_file(&journal.path, r#"{"step":"created","pa"#, true).unwrap();
+
+        let steps = read_steps(root.path()).unwrap().unwrap();
+        assert!(matches!(
+            steps.as_slice(),
+            [Step::Begin { .. }, Step::ConfiguredEnv]
+        ));
+        journal.finish().unwrap();
+    }
+
+    #[test]
+    fn env_vars_without_previous_values() {
+        let step: Step = serde_json::from_str(r#"{"step":"set-env-vars","keys":["A"]}"#).unwrap();
+        assert!(
+            matches!(step, Step::SetEnvVars { keys, previous } if keys == ["A"] && previous.is_empty())
+        );
+    }
+}
@@ -5,12 +5,18 @@
 pub mod components;
 mod custom_instructions;
 pub(crate) mod directories;
+pub mod history;
 pub mod install;
+pub mod journal;
 mod locales;
 pub(crate) mod os;
 pub(crate) mod parser;
 mod path_ext;
+pub mod preflight;
+pub(crate) mod registry;
 pub(crate) mod rustup;
+pub(crate) mod signature;
+pub mod snapshot;
 pub mod toolkit;
 pub(crate) mod tools;
 pub mod try_it;
@@ -10,6 +10,7 @@ pub(crate) mod unix;
 pub(crate) mod windows;
 
 use anyhow::Result;
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use super::GlobalOpts;
@@ -55,3 +56,41 @@ pub(crate) fn remove_from_path(path: &Path) -> Result<()> {
 
     Ok(())
 }
+
+/// Set persistent environment variables, such as the ones that some tools need.
+///
+/// Note this will only set the variables for current process if
+/// [`no_modify_env`](GlobalOpts::no_modify_env) was set to true.
+pub(crate) fn set_env_vars(vars: &BTreeMap<String, String>) -> Result<()> {
+    for (key, val) in vars {
+        std::env::set_var(key, val);
+    }
+    if vars.is_empty() || GlobalOpts::get().no_modify_env {
+        return Ok(());
+    }
+
+    #[cfg(windows)]
+    windows::set_env_vars(vars)?;
+
+    #[cfg(unix)]
+    unix::set_env_vars(vars)?;
+
+    Ok(())
+}
+
+/// Remove persistent environment variables that were set by [`set_env_vars`].
+///
+/// Note this will do nothing if [`no_modify_env`](GlobalOpts::no_modify_env) was set to true.
+pub(crate) fn remove_env_vars(keys: &[&str]) -> Result<()> {
+    if keys.is_empty() || GlobalOpts::get().no_modify_env {
+        return Ok(());
+    }
+
+    #[cfg(windows)]
+    windows::remove_env_vars(keys)?;
+
+    #[cfg(unix)]
+    unix::remove_env_vars(keys)?;
+
+    Ok(())
+}
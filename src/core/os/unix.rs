@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::{env, path::Path};
 
 use crate::core::install::{EnvConfig, InstallConfiguration};
@@ -207,10 +207,66 @@ fn modify_path(path: &Path, remove: bool) -> Result<()> {
     Ok(())
 }
 
+pub(super) fn set_env_vars(vars: &BTreeMap<String, String>) -> Result<()> {
+    let vars = vars
+        .iter()
+        .map(|(key, val)| (key.as_str(), val.to_owned()))
+        .collect::<HashMap<_, _>>();
+    for sh in shell::get_available_shells() {
+        for rc in sh.update_rcs() {
+            let old_content = utils::read_to_string("rc", &rc).unwrap_or_default();
+            let new_content = rc_content_with_env_vars(sh.as_ref(), &old_content, &vars);
+            utils::write_file(&rc, &new_content, false).with_context(|| {
+                format!(
+                    "failed to append environment vars to shell profile: '{}'",
+                    rc.display()
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn remove_env_vars(keys: &[&str]) -> Result<()> {
+    for sh in shell::get_available_shells() {
+        for rc in sh.rcfiles().iter().filter(|rc| rc.is_file()) {
+            let to_remove_summary = keys.join(", ");
+            remove_section_or_warn_(rc, &to_remove_summary, |cont| {
+                rc_content_without_env_vars(sh.as_ref(), &cont, keys)
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Check if a `line` in config section is the one setting env var `key`.
+fn is_setting_env_var(sh: &dyn shell::UnixShell, line: &str, key: &str) -> bool {
+    line.starts_with(&sh.to_env_var_string(key, ""))
+}
+
+/// Remove the lines that set any of the env vars `keys` in the config section,
+/// return `None` if there is no config section.
+fn rc_content_without_env_vars(
+    sh: &dyn shell::UnixShell,
+    old_content: &str,
+    keys: &[&str],
+) -> Option<String> {
+    let existing_configs = get_sub_string_between(
+        old_content,
+        shell::RC_FILE_SECTION_START,
+        shell::RC_FILE_SECTION_END,
+    )?;
+    let new_configs = existing_configs
+        .lines()
+        .filter(|line| !keys.iter().any(|key| is_setting_env_var(sh, line, key)))
+        .collect::<Vec<_>>();
+    Some(old_content.replace(&existing_configs, &new_configs.join("\n")))
+}
+
 fn rc_content_with_env_vars(
     sh: &dyn shell::UnixShell,
     old_content: &str,
-    vars: &HashMap<&'static str, String>,
+    vars: &HashMap<&str, String>,
 ) -> String {
     // converts env vars such as [(KEY, value), (KEY2, value2)] to ["export KEY='value'"", "export KEY2='value2'"]
     let vars_as_exports = vars.iter().map(|(k, v)| sh.to_env_var_string(k, v));
@@ -223,7 +279,7 @@ fn rc_content_with_env_vars(
         // Remove the old env var config
         let mut new_configs = existing_configs
             .lines()
-            .filter(|line| !vars.keys().any(|key| line.contains(key)))
+            .filter(|line| !vars.keys().any(|key| is_setting_env_var(sh, line, key)))
             .map(|s| s.to_string())
             .collect::<Vec<_>>();
         // push new env var config, even though they have the same value
@@ -329,7 +385,7 @@ mod shell {
         fn update_rcs(&self) -> Vec<PathBuf>;
 
         /// Format a shell command to set env var.
-        fn to_env_var_string(&self, key: &'static str, val: &str) -> String {
+        fn to_env_var_string(&self, key: &str, val: &str) -> String {
             format!("export {key}={val}")
         }
 
@@ -488,7 +544,7 @@ mod shell {
             res
         }
 
-        fn to_env_var_string(&self, key: &'static str, val: &str) -> String {
+        fn to_env_var_string(&self, key: &str, val: &str) -> String {
             format!("set -Ux {key} {val}")
         }
 
@@ -542,7 +598,7 @@ mod tests {
     use std::path::PathBuf;
 
     use super::{
-        rc_content_with_path,
+        rc_content_with_env_vars, rc_content_with_path, rc_content_without_env_vars,
         shell::{self, UnixShell},
     };
 
@@ -882,4 +938,35 @@ export PATH=/some/user/defined/bin:$PATH
 "#
         );
     }
+
+    #[test]
+    fn add_and_remove_tool_env_vars() {
+        let existing_rc = r#"\
+alias autoremove='sudo pacman -Rcns $(pacman -Qdtq)'
+
+# ===== rustup config section START =====
+export CARGO_HOME='/path/to/cargo'
+export RUSTUP_HOME='/path/to/rustup'
+# ===== rustup config section END =====
+"#;
+
+        let shell = shell::Bash;
+        let vars = [("HOME", "/path/to/home".to_string())].into();
+        let new_content = rc_content_with_env_vars(&shell, existing_rc, &vars);
+        assert_eq!(
+            new_content,
+            r#"\
+alias autoremove='sudo pacman -Rcns $(pacman -Qdtq)'
+
+# ===== rustup config section START =====
+export CARGO_HOME='/path/to/cargo'
+export RUSTUP_HOME='/path/to/rustup'
+export HOME=/path/to/home
+# ===== rustup config section END =====
+"#
+        );
+
+        let new_content = rc_content_without_env_vars(&shell, &new_content, &["HOME"]).unwrap();
+        assert_eq!(new_content, existing_rc);
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::env::current_exe;
 
 use crate::core::directories::RimDir;
@@ -43,6 +44,12 @@ impl Uninstallation for UninstallConfiguration<'_> {
         for var_to_remove in crate::core::ALL_VARS {
             set_env_var(var_to_remove, vec![])?;
         }
+        // Remove the variables of tools as well, in case any of them are left.
+        for tool in self.install_record.tools.values() {
+            for key in tool.env.keys() {
+                set_env_var(key, vec![])?;
+            }
+        }
 
         update_env();
 
@@ -76,6 +83,22 @@ impl Uninstallation for UninstallConfiguration<'_> {
     }
 }
 
+pub(super) fn set_env_vars(vars: &BTreeMap<String, String>) -> Result<()> {
+    for (key, val) in vars {
+        set_env_var(key, val.encode_utf16().collect())?;
+    }
+    update_env();
+    Ok(())
+}
+
+pub(super) fn remove_env_vars(keys: &[&str]) -> Result<()> {
+    for key in keys {
+        set_env_var(key, vec![])?;
+    }
+    update_env();
+    Ok(())
+}
+
 /// Module containing functions that are modified from `rustup`.
 pub(crate) mod rustup {
     use std::env;
@@ -93,7 +116,7 @@ pub(crate) mod rustup {
 
     static UNINSTALL_ENTRY: OnceLock<String> = OnceLock::new();
 
-    pub(super) fn uninstall_entry() -> &'static str {
+    pub(crate) fn uninstall_entry() -> &'static str {
         UNINSTALL_ENTRY.get_or_init(|| {
             format!(
                 "Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\{}",
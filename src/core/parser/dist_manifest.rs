@@ -23,6 +23,8 @@ pub struct DistPackage {
     pub desc: Option<String>,
     pub info: Option<String>,
     pub manifest_url: Url,
+    /// The minimum version of manager that is able to install this toolkit.
+    pub min_manager_version: Option<String>,
 }
 
 impl TomlParser for DistManifest {
@@ -48,6 +50,7 @@ mod tests {
             desc: desc.map(ToString::to_string),
             info: info.map(ToString::to_string),
             manifest_url: url.parse().unwrap(),
+            min_manager_version: None,
         }
     }
 
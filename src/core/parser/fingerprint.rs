@@ -2,11 +2,16 @@ use anyhow::{anyhow, Context, Result};
 use indexmap::IndexMap;
 use log::trace;
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use url::Url;
 
 use crate::{core::tools::ToolKind, setter, utils};
 
-use super::{toolset_manifest::ToolsetManifest, TomlParser};
+use super::{
+    toolset_manifest::{Hooks, ToolsetManifest},
+    TomlParser,
+};
 
 /// Re-load fingerprint file just to get the list of installed tools,
 /// therefore we can use this list to uninstall, while avoiding race condition.
@@ -26,6 +31,9 @@ pub struct InstallationRecord {
     pub rust: Option<RustRecord>,
     #[serde(default)]
     pub tools: IndexMap<String, ToolRecord>,
+    /// Names of the licenses that were accepted by the user, keyed by the tool names.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub accepted_licenses: BTreeMap<String, String>,
 }
 
 impl TomlParser for InstallationRecord {
@@ -104,16 +112,24 @@ impl InstallationRecord {
         self.version = None;
     }
 
-    pub(crate) fn add_rust_record(&mut self, version: &str, components: &[String]) {
+    pub(crate) fn add_rust_record(
+        &mut self,
+        version: &str,
+        components: &[String],
+        targets: &[String],
+    ) {
         self.rust = Some(RustRecord {
             version: version.to_string(),
             components: components.to_vec(),
+            targets: targets.to_vec(),
         });
     }
 
-    pub(crate) fn update_rust(&mut self, version: &str) {
+    pub(crate) fn update_rust(&mut self, version: &str, components: &[String], targets: &[String]) {
         if let Some(rust) = self.rust.as_mut() {
             rust.version = version.into();
+            rust.components = components.to_vec();
+            rust.targets = targets.to_vec();
             trace!("toolchain installation record was updated to '{version}'");
         }
     }
@@ -158,6 +174,13 @@ impl InstallationRecord {
         })
     }
 
+    /// Return an iterator of installed (cross compilation) targets of the toolchain.
+    pub fn installed_targets(&self) -> impl Iterator<Item = &str> {
+        self.rust
+            .iter()
+            .flat_map(|rr| rr.targets.iter().map(|s| s.as_str()))
+    }
+
     pub(crate) fn print_installation(&self) -> String {
         let mut installed = String::new();
         if let Some(rust) = &self.rust {
@@ -169,9 +192,29 @@ impl InstallationRecord {
         installed
     }
 
+    /// Return an iterator of installed tools' names that depend on the given tool.
+    pub fn dependents_of<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.tools
+            .iter()
+            .filter(move |(_, rec)| rec.dependencies.iter().any(|dep| dep == name))
+            .map(|(dependent, _)| dependent.as_str())
+    }
+
     pub fn get_tool_version(&self, name: &str) -> Option<&str> {
         self.tools.get(name).and_then(|rec| rec.version.as_deref())
     }
+
+    /// Check if the user has accepted the license (by its name) of a tool before.
+    pub fn has_accepted_license(&self, tool: &str, license: &str) -> bool {
+        self.accepted_licenses
+            .get(tool)
+            .is_some_and(|l| l == license)
+    }
+
+    pub(crate) fn accept_license(&mut self, tool: &str, license: &str) {
+        self.accepted_licenses
+            .insert(tool.to_string(), license.to_string());
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -180,18 +223,24 @@ pub struct RustRecord {
     version: String,
     #[serde(default)]
     pub(crate) components: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) targets: Vec<String>,
 }
 
 impl RustRecord {
     pub(crate) fn print_rust_info(&self) -> String {
-        format!(
+        let mut info = format!(
             "rust-version: {}\ncomponents: {:?}\n",
             self.version, self.components
-        )
+        );
+        if !self.targets.is_empty() {
+            info.push_str(&format!("targets: {:?}\n", self.targets));
+        }
+        info
     }
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct ToolRecord {
     #[deprecated(since = "0.3.1", note = "use `.tool_kind()` instead")]
@@ -206,6 +255,24 @@ pub struct ToolRecord {
     version: Option<String>,
     #[serde(default)]
     pub(crate) paths: Vec<PathBuf>,
+    /// Names of other tools that this tool depends on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) dependencies: Vec<String>,
+    /// The url (or one of the mirrors) that the package of this tool was downloaded from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) url: Option<Url>,
+    /// The git commit that this tool was built from, if it was installed from a git repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) rev: Option<String>,
+    /// SHA-256 checksum of the package that this tool was downloaded as.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) sha256: Option<String>,
+    /// Environment variables that were set for this tool, which will be removed after uninstallation.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) env: BTreeMap<String, String>,
+    /// Commands to run when uninstalling this tool, with placeholders already expanded.
+    #[serde(default, flatten)]
+    pub(crate) hooks: Hooks,
 }
 
 impl ToolRecord {
@@ -229,6 +296,12 @@ impl ToolRecord {
     }
 
     setter!(paths(self, Vec<PathBuf>));
+    setter!(dependencies(self, Vec<String>));
+    setter!(url(self, Option<Url>));
+    setter!(rev(self, Option<String>));
+    setter!(sha256(self, Option<String>));
+    setter!(env(self, BTreeMap<String, String>));
+    setter!(hooks(self, Hooks));
     setter!(version(self, ver: Option<impl Into<String>>) { ver.map(Into::into) });
 }
 
@@ -274,7 +347,7 @@ mod tests {
         let mut fp = InstallationRecord::load(&install_dir).unwrap();
         let rust_components = vec![String::from("rustfmt"), String::from("cargo")];
 
-        fp.add_rust_record("stable", &rust_components);
+        fp.add_rust_record("stable", &rust_components, &[]);
         fp.add_tool_record(
             "aaa",
             ToolRecord::new(ToolKind::Custom).paths(vec![install_dir.join("aaa")]),
@@ -298,6 +371,41 @@ paths = [{QUOTE}{}{QUOTE}]
         assert_eq!(v0, fp.to_toml().unwrap());
     }
 
+    #[test]
+    fn with_rust_targets() {
+        let input = r#"
+root = '/path/to/something'
+
+[rust]
+version = "stable"
+components = []
+targets = ["wasm32-unknown-unknown"]"#;
+
+        let record = InstallationRecord::from_str(input).unwrap();
+        assert_eq!(
+            record.installed_targets().collect::<Vec<_>>(),
+            ["wasm32-unknown-unknown"]
+        );
+    }
+
+    #[test]
+    fn with_downloaded_url() {
+        let input = r#"
+root = '/path/to/something'
+
+[tools.aaa]
+kind = "custom"
+paths = []
+url = "https://mirror.example.com/aaa.zip"
+"#;
+
+        let record = InstallationRecord::from_str(input).unwrap();
+        assert_eq!(
+            record.tools["aaa"].url.as_ref().map(Url::as_str),
+            Some("https://mirror.example.com/aaa.zip")
+        );
+    }
+
     #[test]
     fn with_name_and_ver() {
         let input = r#"
@@ -377,6 +485,25 @@ paths = []
         assert_eq!(ser, expected);
     }
 
+    #[test]
+    fn with_accepted_licenses() {
+        let input = r#"
+root = '/path/to/something'
+
+[accepted_licenses]
+a = "EULA"
+"#;
+
+        let mut record = InstallationRecord::from_str(input).unwrap();
+        assert!(record.has_accepted_license("a", "EULA"));
+        assert!(!record.has_accepted_license("a", "MIT"));
+        assert!(!record.has_accepted_license("b", "EULA"));
+
+        record.accept_license("b", "MIT");
+        assert!(record.has_accepted_license("b", "MIT"));
+        assert!(record.to_toml().unwrap().contains("b = \"MIT\""));
+    }
+
     #[test]
     fn with_tool_version() {
         let input = r#"
@@ -0,0 +1,236 @@
+use anyhow::{Context, Result};
+use log::trace;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::TomlParser;
+use crate::utils::{self, HashAlgorithm};
+
+/// Holds every file installed by each tool, along with its size and hash,
+/// which is used to find out the files that were removed or modified after installation.
+///
+/// This is kept separately from the [`InstallationRecord`](super::fingerprint::InstallationRecord)
+/// because it could get very large for tools that have a lot of files.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct InstalledFiles {
+    #[serde(default)]
+    tools: BTreeMap<String, Vec<FileEntry>>,
+}
+
+impl TomlParser for InstalledFiles {
+    const FILENAME: &str = ".installed-files.toml";
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct FileEntry {
+    /// Path to the file, relative to the installation root if it's inside of it.
+    pub path: PathBuf,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// A problem of an installed file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileIssue {
+    Missing(PathBuf),
+    Modified(PathBuf),
+}
+
+impl fmt::Display for FileIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::Missing(path) => t!("file_missing", path = path.display()),
+            Self::Modified(path) => t!("file_modified", path = path.display()),
+        };
+        write!(f, "{msg}")
+    }
+}
+
+impl InstalledFiles {
+    /// Load the file records under `root`, or an empty one if there isn't any,
+    /// such as an installation made by an older version.
+    pub(crate) fn load_from_root(root: &Path) -> Result<Self> {
+        let path = root.join(Self::FILENAME);
+        if path.is_file() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub(crate) fn write(&self, root: &Path) -> Result<()> {
+        let path = root.join(Self::FILENAME);
+        let content = self
+            .to_toml()
+            .context("unable to serialize installed file records")?;
+        trace!("writing installed file records into '{}'", path.display());
+        utils::write_file(&path, &content, false)
+    }
+
+    /// Record every file under the given `paths` (which could be files or directories)
+    /// as the files of tool `name`, replacing the previous records of it.
+    pub(crate) fn add_tool(&mut self, root: &Path, name: &str, paths: &[PathBuf]) -> Result<()> {
+        let mut entries = vec![];
+        for path in paths {
+            let files = if path.is_dir() {
+                utils::walk_dir(path, true)?
+            } else {
+                vec![path.clone()]
+            };
+            for file in files.into_iter().filter(|p| p.is_file()) {
+                entries.push(FileEntry {
+                    size: file.metadata()?.len(),
+                    sha256: utils::file_digest(&file, HashAlgorithm::Sha256)?,
+                    path: file.strip_prefix(root).unwrap_or(&file).to_path_buf(),
+                });
+            }
+        }
+        self.tools.insert(name.to_string(), entries);
+        Ok(())
+    }
+
+    pub(crate) fn remove_tool(&mut self, name: &str) {
+        self.tools.remove(name);
+    }
+
+    /// Check if the files of tool `name` exists and are not modified,
+    /// return `None` if there's no record of this tool.
+    pub fn check_tool(&self, root: &Path, name: &str) -> Option<Vec<FileIssue>> {
+        let entries = self.tools.get(name)?;
+        let issues = entries
+            .iter()
+            .filter_map(|entry| {
+                let path = root.join(&entry.path);
+                let Some(meta) = path.metadata().ok().filter(|m| m.is_file()) else {
+                    return Some(FileIssue::Missing(path));
+                };
+                let modified = meta.len() != entry.size
+                    || utils::file_digest(&path, HashAlgorithm::Sha256)
+                        .map_or(true, |digest| digest != entry.sha256);
+                modified.then_some(FileIssue::Modified(path))
+            })
+            .collect();
+        Some(issues)
+    }
+}
+
+#[derive(Deserialize)]
+struct CargoInstalls {
+    installs: BTreeMap<String, CargoInstall>,
+}
+
+#[derive(Deserialize)]
+struct CargoInstall {
+    #[serde(default)]
+    bins: Vec<String>,
+}
+
+/// Get the crates installed by `cargo install`, which are tracked by cargo
+/// in the `.crates2.json` file under `cargo_home`.
+///
+/// The keys are like `cargo-nextest 0.9.85 (registry+https://...)`.
+fn cargo_installs(cargo_home: &Path) -> Result<BTreeMap<String, CargoInstall>> {
+    let path = cargo_home.join(".crates2.json");
+    if !path.is_file() {
+        return Ok(BTreeMap::new());
+    }
+    let raw = utils::read_to_string("cargo install tracking", &path)?;
+    let tracked: CargoInstalls = serde_json::from_str(&raw)
+        .with_context(|| format!("unable to parse '{}'", path.display()))?;
+    Ok(tracked.installs)
+}
+
+/// Get the binaries installed by `cargo install` for crate `name`.
+pub(crate) fn cargo_tool_bins(cargo_home: &Path, name: &str) -> Result<Vec<PathBuf>> {
+    Ok(cargo_installs(cargo_home)?
+        .into_iter()
+        .filter(|(key, _)| key.split(' ').next() == Some(name))
+        .flat_map(|(_, install)| install.bins)
+        .map(|bin| cargo_home.join("bin").join(bin))
+        .collect())
+}
+
+/// Get the git commit that crate `name` was built from, if it was installed by
+/// `cargo install --git`, whose key is like `tool 0.1.0 (git+https://...?tag=v0.1.0#<commit>)`.
+pub(crate) fn cargo_tool_git_rev(cargo_home: &Path, name: &str) -> Result<Option<String>> {
+    Ok(cargo_installs(cargo_home)?
+        .into_keys()
+        .filter(|key| key.split(' ').next() == Some(name))
+        .find_map(|key| {
+            let (_, source) = key.split_once(" (git+")?;
+            let (_, rev) = source.trim_end_matches(')').rsplit_once('#')?;
+            Some(rev.to_string())
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_check_files() {
+        let root = tempfile::tempdir().unwrap();
+        let tool_dir = root.path().join("tools").join("aaa");
+        utils::ensure_dir(tool_dir.join("bin")).unwrap();
+        utils::write_file(tool_dir.join("bin").join("aaa"), "binary", false).unwrap();
+        utils::write_file(tool_dir.join("README"), "readme", false).unwrap();
+
+        let mut files = InstalledFiles::default();
+        files
+            .add_tool(root.path(), "aaa", std::slice::from_ref(&tool_dir))
+            .unwrap();
+        files.write(root.path()).unwrap();
+
+        let files = InstalledFiles::load_from_root(root.path()).unwrap();
+        assert_eq!(files.tools["aaa"].len(), 2);
+        assert!(files.tools["aaa"].iter().all(|e| e.path.is_relative()));
+        assert_eq!(files.check_tool(root.path(), "aaa"), Some(vec![]));
+        assert_eq!(files.check_tool(root.path(), "bbb"), None);
+
+        utils::write_file(tool_dir.join("README"), "tampered", false).unwrap();
+        std::fs::remove_file(tool_dir.join("bin").join("aaa")).unwrap();
+        let mut issues = files.check_tool(root.path(), "aaa").unwrap();
+        issues.sort_by_key(|i| matches!(i, FileIssue::Modified(_)));
+        assert_eq!(
+            issues,
+            [
+                FileIssue::Missing(tool_dir.join("bin").join("aaa")),
+                FileIssue::Modified(tool_dir.join("README")),
+            ]
+        );
+    }
+
+    #[test]
+    fn bins_of_cargo_tools() {
+        let cargo_home = tempfile::tempdir().unwrap();
+        let tracking = r#"{"installs":{
+"cargo-nextest 0.9.85 (registry+https://github.com/rust-lang/crates.io-index)":{"version_req":null,"bins":["cargo-nextest"],"features":[],"all_features":false,"no_default_features":false,"profile":"release","target":"x86_64-unknown-linux-gnu","rustc":""},
+"cargo-nextest-extra 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)":{"bins":["extra"]}
+}}"#;
+        utils::write_file(cargo_home.path().join(".crates2.json"), tracking, false).unwrap();
+
+        let bins = cargo_tool_bins(cargo_home.path(), "cargo-nextest").unwrap();
+        assert_eq!(bins, [cargo_home.path().join("bin").join("cargo-nextest")]);
+        assert!(cargo_tool_bins(cargo_home.path(), "foo")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn git_rev_of_cargo_tools() {
+        let cargo_home = tempfile::tempdir().unwrap();
+        let tracking = r#"{"installs":{
+"cargo-nextest 0.9.85 (registry+https://github.com/rust-lang/crates.io-index)":{"bins":["cargo-nextest"]},
+"tool 0.1.0 (git+https://example.com/tool.git?tag=v0.1.0#0123abcd)":{"bins":["tool"]}
+}}"#;
+        utils::write_file(cargo_home.path().join(".crates2.json"), tracking, false).unwrap();
+
+        let rev = cargo_tool_git_rev(cargo_home.path(), "tool").unwrap();
+        assert_eq!(rev.as_deref(), Some("0123abcd"));
+        assert!(cargo_tool_git_rev(cargo_home.path(), "cargo-nextest")
+            .unwrap()
+            .is_none());
+    }
+}
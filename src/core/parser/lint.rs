@@ -0,0 +1,894 @@
+//! Lints of toolset manifest, which report the mistakes in a manifest file along with their
+//! locations, so that they can be caught before shipping the manifest to users.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use cfg_expr::Expression;
+use toml_edit::{ImDocument, Item, TableLike};
+
+use super::toolset_manifest::{ByteSize, ToolsetManifest, Variables};
+use super::TomlParser;
+use crate::utils;
+
+const ROOT_KEYS: &[&str] = &[
+    "name",
+    "version",
+    "min-manager-version",
+    "rust",
+    "tools",
+    "proxy",
+    "extends",
+    "remove",
+    "presets",
+];
+const RUST_KEYS: &[&str] = &[
+    "version",
+    "profile",
+    "components",
+    "optional-components",
+    "targets",
+    "optional-targets",
+    "name",
+    "group",
+    "offline-dist-server",
+    "rustup",
+];
+const PROFILE_KEYS: &[&str] = &["name", "verbose-name", "description"];
+const TOOLS_KEYS: &[&str] = &["descriptions", "group", "target"];
+const PROXY_KEYS: &[&str] = &["http", "https", "no-proxy", "no_proxy"];
+const REMOVE_KEYS: &[&str] = &["components", "tools", "groups"];
+const PRESET_KEYS: &[&str] = &["description", "components"];
+/// The keys that decide which kind of source a tool is installed from.
+const TOOL_SOURCE_KEYS: &[&str] = &["ver", "git", "path", "url"];
+/// The keys that are available to tools of any source.
+const TOOL_COMMON_KEYS: &[&str] = &[
+    "required",
+    "optional",
+    "depends-on",
+    "conflicts-with",
+    "license",
+    "size",
+    "env",
+    "post-install",
+    "pre-uninstall",
+    "post-uninstall",
+];
+
+/// Get the list of keys that are allowed in a tool of a certain source (`ver`, `git` etc.).
+fn tool_keys(source: &str) -> &'static [&'static str] {
+    match source {
+        "ver" => &[
+            "ver",
+            "features",
+            "no-default-features",
+            "all-features",
+            "locked",
+            "bins",
+            "registry",
+            "prebuilt",
+        ],
+        "git" => &[
+            "git",
+            "branch",
+            "tag",
+            "rev",
+            "features",
+            "no-default-features",
+            "all-features",
+            "locked",
+            "bins",
+            "registry",
+            "prebuilt",
+        ],
+        "path" => &["path", "version", "sha256", "sha512"],
+        "url" => &["url", "version", "sha256", "sha512"],
+        _ => &[],
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Additional information that is not a problem, such as the expanded value of a placeholder.
+    Note,
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Note => write!(f, "note"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single problem found in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// The (1-based) line and column number of where the problem is.
+    pub position: Option<(usize, usize)>,
+}
+
+/// All the problems found in a manifest file.
+#[derive(Debug)]
+pub struct LintReport {
+    pub path: PathBuf,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LintReport {
+    pub fn errors(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    }
+}
+
+impl Display for LintReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for diag in &self.diagnostics {
+            writeln!(f, "{}: {}", diag.severity, diag.message)?;
+            if let Some((line, col)) = diag.position {
+                writeln!(f, "  --> {}:{line}:{col}", self.path.display())?;
+            } else {
+                writeln!(f, "  --> {}", self.path.display())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check a toolset manifest file for common mistakes, including:
+///
+/// - Syntax errors, or values that cannot be deserialized.
+/// - Unknown keys, which would otherwise be ignored silently.
+/// - Tools that have ambiguous or missing sources.
+/// - Tools without descriptions.
+/// - Group members or dependencies that are not defined for any target.
+/// - Relative paths that do not exist.
+/// - URL tools without versions.
+/// - Invalid `cfg(...)` target keys.
+/// - `${...}` placeholders that cannot be expanded, the expanded values will be reported as notes.
+pub fn lint_toolset_manifest(path: &Path) -> Result<LintReport> {
+    let raw = utils::read_to_string("manifest", path)?;
+    let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let mut linter = Linter {
+        raw: &raw,
+        root,
+        version: None,
+        rust_version: String::new(),
+        diagnostics: vec![],
+    };
+    linter.lint(path);
+
+    Ok(LintReport {
+        path: path.to_path_buf(),
+        diagnostics: linter.diagnostics,
+    })
+}
+
+struct Linter<'a> {
+    raw: &'a str,
+    /// The directory containing the manifest, used to resolve relative paths.
+    root: PathBuf,
+    /// The product version, used to expand `${version}` placeholders.
+    version: Option<String>,
+    /// The toolchain version, used to expand `${rust.version}` placeholders.
+    rust_version: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn lint(&mut self, path: &Path) {
+        let doc = match ImDocument::parse(self.raw) {
+            Ok(doc) => doc,
+            Err(e) => {
+                self.report(Severity::Error, e.span(), e.message());
+                return;
+            }
+        };
+        let root = doc.as_table();
+        // Load the manifest again to include the tools from base manifest (`extends`), if there's any.
+        let composed = ToolsetManifest::load(path);
+        if root.contains_key("extends") {
+            // an overlay may omit required fields, such as `[rust]`, as long as its base has them,
+            // so it can only be checked after being merged onto its base.
+            if let Err(e) = &composed {
+                self.report(Severity::Error, None, format!("{e:#}"));
+            }
+        } else if let Err(e) = toml::from_str::<ToolsetManifest>(self.raw) {
+            self.report(Severity::Error, e.span(), e.message());
+        }
+        let composed = composed.ok();
+
+        self.version = root
+            .get("version")
+            .and_then(Item::as_str)
+            .map(ToOwned::to_owned)
+            .or_else(|| composed.as_ref().and_then(|m| m.version.clone()));
+        self.rust_version = root
+            .get("rust")
+            .and_then(|rust| rust.get("version"))
+            .and_then(Item::as_str)
+            .or_else(|| composed.as_ref().map(|m| m.rust_version()))
+            .unwrap_or_default()
+            .to_string();
+
+        self.check_keys(root, ROOT_KEYS, "manifest");
+        if let Some(min_ver) = root.get("min-manager-version").and_then(Item::as_value) {
+            if min_ver
+                .as_str()
+                .map_or(true, |v| semver::Version::parse(v).is_err())
+            {
+                self.report(
+                    Severity::Error,
+                    min_ver.span(),
+                    "`min-manager-version` must be a valid semantic version, such as \"0.3.0\"",
+                );
+            }
+        }
+        if let Some(rust) = root.get("rust").and_then(Item::as_table_like) {
+            self.check_keys(rust, RUST_KEYS, "`rust`");
+            if let Some(profile) = rust.get("profile").and_then(Item::as_table_like) {
+                self.check_keys(profile, PROFILE_KEYS, "`rust.profile`");
+            }
+            if let Some(server) = rust.get("offline-dist-server").and_then(Item::as_value) {
+                let vars = self.variables(env!("TARGET"));
+                self.expand(server, &vars, "`rust.offline-dist-server`");
+            }
+            if let Some(rustup) = rust.get("rustup").and_then(Item::as_table_like) {
+                for (target, path) in rustup.iter() {
+                    if let Some(path) = path.as_value() {
+                        let vars = self.variables(target);
+                        self.expand(path, &vars, &format!("`rust.rustup.{target}`"));
+                    }
+                }
+            }
+        }
+        if let Some(proxy) = root.get("proxy").and_then(Item::as_table_like) {
+            self.check_keys(proxy, PROXY_KEYS, "`proxy`");
+        }
+        if let Some(remove) = root.get("remove").and_then(Item::as_table_like) {
+            self.check_keys(remove, REMOVE_KEYS, "`remove`");
+        }
+        if let Some(tools) = root.get("tools").and_then(Item::as_table_like) {
+            self.check_keys(tools, TOOLS_KEYS, "`tools`");
+            self.lint_tools(tools, composed.as_ref());
+        }
+        if let Some(presets) = root.get("presets").and_then(Item::as_table_like) {
+            self.lint_presets(presets, root, composed.as_ref());
+        }
+    }
+
+    fn lint_presets(
+        &mut self,
+        presets: &dyn TableLike,
+        root: &dyn TableLike,
+        composed: Option<&ToolsetManifest>,
+    ) {
+        // names of the components that could be listed in a preset
+        let mut known: HashSet<&str> = root
+            .get("tools")
+            .and_then(|tools| tools.get("target"))
+            .and_then(Item::as_table_like)
+            .iter()
+            .flat_map(|targets| targets.iter())
+            .filter_map(|(_, map)| map.as_table_like())
+            .flat_map(|map| map.iter().map(|(name, _)| name))
+            .collect();
+        if let Some(rust) = root.get("rust") {
+            for key in ["components", "optional-components", "optional-targets"] {
+                let names = rust.get(key).and_then(Item::as_array);
+                known.extend(
+                    names
+                        .iter()
+                        .flat_map(|a| a.iter())
+                        .filter_map(|v| v.as_str()),
+                );
+            }
+        }
+        if let Some(manifest) = composed {
+            known.extend(manifest.all_tool_names());
+            known.extend(manifest.rust.components.iter().map(String::as_str));
+            known.extend(
+                manifest
+                    .optional_toolchain_components()
+                    .iter()
+                    .map(String::as_str),
+            );
+            known.extend(
+                manifest
+                    .optional_toolchain_targets()
+                    .iter()
+                    .map(String::as_str),
+            );
+        }
+
+        for (preset_name, preset) in presets.iter() {
+            let Some(preset) = preset.as_table_like() else {
+                self.report(
+                    Severity::Error,
+                    presets.key(preset_name).and_then(|k| k.span()),
+                    format!("preset `{preset_name}` must be a table"),
+                );
+                continue;
+            };
+            self.check_keys(preset, PRESET_KEYS, &format!("preset `{preset_name}`"));
+            let Some(components) = preset.get("components").and_then(Item::as_array) else {
+                continue;
+            };
+            for component in components {
+                let Some(name) = component.as_str() else {
+                    continue;
+                };
+                if !known.contains(name) {
+                    self.report(
+                        Severity::Error,
+                        component.span(),
+                        format!("preset `{preset_name}` contains `{name}`, which is not a known component"),
+                    );
+                }
+            }
+        }
+    }
+
+    fn lint_tools(&mut self, tools: &dyn TableLike, composed: Option<&ToolsetManifest>) {
+        let targets = tools.get("target").and_then(Item::as_table_like);
+
+        let mut known_tools: HashSet<&str> = targets
+            .iter()
+            .flat_map(|targets| targets.iter())
+            .filter_map(|(_, map)| map.as_table_like())
+            .flat_map(|map| map.iter().map(|(name, _)| name))
+            .collect();
+        let mut described: HashSet<&str> = tools
+            .get("descriptions")
+            .and_then(Item::as_table_like)
+            .map(|desc| desc.iter().map(|(name, _)| name).collect())
+            .unwrap_or_default();
+        if let Some(manifest) = composed {
+            known_tools.extend(manifest.all_tool_names());
+            described.extend(
+                manifest
+                    .all_tool_names()
+                    .into_iter()
+                    .filter(|name| manifest.get_tool_description(name).is_some()),
+            );
+        }
+
+        if let Some(targets) = targets {
+            let mut reported_desc = HashSet::new();
+            for (target, item) in targets.iter() {
+                let target_span = targets.key(target).and_then(|k| k.span());
+                if target.starts_with("cfg(") {
+                    if let Err(e) = Expression::parse(target) {
+                        self.report(
+                            Severity::Error,
+                            target_span,
+                            format!("invalid cfg expression `{target}`: {}", e.reason),
+                        );
+                    }
+                }
+                let Some(map) = item.as_table_like() else {
+                    continue;
+                };
+                for (name, tool) in map.iter() {
+                    let name_span = map.key(name).and_then(|k| k.span());
+                    if !described.contains(name) && reported_desc.insert(name) {
+                        self.report(
+                            Severity::Warning,
+                            name_span.clone(),
+                            format!("tool `{name}` does not have a description"),
+                        );
+                    }
+                    if let Some(tool) = tool.as_table_like() {
+                        let version = ["version", "ver"]
+                            .iter()
+                            .find_map(|key| tool.get(key).and_then(Item::as_str));
+                        let vars = self.variables(target).with_version(version);
+                        self.lint_tool(name, name_span, tool, &vars, &known_tools);
+                    }
+                }
+            }
+        }
+
+        if let Some(groups) = tools.get("group").and_then(Item::as_table_like) {
+            for (group, members) in groups.iter() {
+                // `{ members = [...], choose-one = true }` or just a list of members
+                let members = match members.as_table_like() {
+                    Some(detailed) => detailed.get("members").and_then(Item::as_array),
+                    None => members.as_array(),
+                };
+                let Some(members) = members else {
+                    continue;
+                };
+                for member in members {
+                    let Some(name) = member.as_str() else {
+                        continue;
+                    };
+                    if !known_tools.contains(name) {
+                        self.report(
+                            Severity::Error,
+                            member.span(),
+                            format!("group `{group}` contains `{name}`, which is not defined for any target"),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn lint_tool(
+        &mut self,
+        name: &str,
+        name_span: Option<Range<usize>>,
+        tool: &dyn TableLike,
+        vars: &Variables,
+        known_tools: &HashSet<&str>,
+    ) {
+        let sources = TOOL_SOURCE_KEYS
+            .iter()
+            .copied()
+            .filter(|key| tool.contains_key(key))
+            .collect::<Vec<_>>();
+        let source = match sources.as_slice() {
+            [source] => *source,
+            [] => {
+                self.report(
+                    Severity::Error,
+                    name_span,
+                    format!("tool `{name}` must have one of `ver`, `git`, `path` or `url`"),
+                );
+                return;
+            }
+            _ => {
+                self.report(
+                    Severity::Error,
+                    name_span,
+                    format!(
+                        "tool `{name}` has multiple sources (`{}`), only one is allowed",
+                        sources.join("`, `")
+                    ),
+                );
+                return;
+            }
+        };
+
+        let allowed = tool_keys(source);
+        for (key, _) in tool.iter() {
+            if !allowed.contains(&key) && !TOOL_COMMON_KEYS.contains(&key) {
+                self.report(
+                    Severity::Error,
+                    tool.key(key).and_then(|k| k.span()),
+                    format!("unknown key `{key}` for tool `{name}` installed from `{source}`"),
+                );
+            }
+        }
+
+        match source {
+            "path" => {
+                if let Some(value) = tool.get("path").and_then(Item::as_value) {
+                    let expanded = self.expand(value, vars, &format!("path of tool `{name}`"));
+                    if let Some(path) = expanded.as_deref().map(Path::new) {
+                        if path.is_relative() && !self.root.join(path).exists() {
+                            self.report(
+                                Severity::Error,
+                                value.span(),
+                                format!(
+                                    "path `{}` of tool `{name}` does not exist",
+                                    path.display()
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+            "url" => {
+                match tool.get("url").and_then(Item::as_value) {
+                    // a list of mirrors
+                    Some(toml_edit::Value::Array(mirrors)) if mirrors.is_empty() => {
+                        self.report(
+                            Severity::Error,
+                            mirrors.span(),
+                            format!("tool `{name}` has an empty list of urls"),
+                        );
+                    }
+                    Some(toml_edit::Value::Array(mirrors)) => {
+                        for mirror in mirrors {
+                            self.expand(mirror, vars, &format!("mirror of tool `{name}`"));
+                        }
+                    }
+                    Some(value) => {
+                        self.expand(value, vars, &format!("url of tool `{name}`"));
+                    }
+                    None => (),
+                }
+                if !tool.contains_key("version") {
+                    self.report(
+                        Severity::Warning,
+                        name_span,
+                        format!(
+                            "tool `{name}` is installed from url but does not have a `version`"
+                        ),
+                    );
+                }
+            }
+            _ => (),
+        }
+
+        // `${version}` in prebuilt archives is expanded after resolving the version requirement
+        let version = ["version", "ver"]
+            .iter()
+            .find_map(|key| tool.get(key).and_then(Item::as_str));
+        let prebuilt_vars = vars.clone().keep_unresolved_version(version);
+        match tool.get("prebuilt").and_then(Item::as_value) {
+            Some(toml_edit::Value::Array(mirrors)) => {
+                for mirror in mirrors {
+                    self.expand(
+                        mirror,
+                        &prebuilt_vars,
+                        &format!("prebuilt archive of tool `{name}`"),
+                    );
+                }
+            }
+            Some(value) => {
+                self.expand(
+                    value,
+                    &prebuilt_vars,
+                    &format!("prebuilt archive of tool `{name}`"),
+                );
+            }
+            None => (),
+        }
+
+        if let Some(license) = tool.get("license").and_then(Item::as_table_like) {
+            self.check_keys(
+                license,
+                &["name", "url", "path"],
+                &format!("license of tool `{name}`"),
+            );
+            if !license.contains_key("name") {
+                self.report(
+                    Severity::Error,
+                    tool.key("license").and_then(|k| k.span()),
+                    format!("license of tool `{name}` does not have a `name`"),
+                );
+            }
+            if let Some(path) = license.get("path").and_then(Item::as_value) {
+                if let Some(p) = path.as_str().map(Path::new) {
+                    if p.is_relative() && !self.root.join(p).exists() {
+                        self.report(
+                            Severity::Error,
+                            path.span(),
+                            format!(
+                                "license file `{}` of tool `{name}` does not exist",
+                                p.display()
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(size) = tool.get("size").and_then(Item::as_value) {
+            let valid = match size {
+                toml_edit::Value::Integer(bytes) => *bytes.value() >= 0,
+                toml_edit::Value::String(s) => s.value().parse::<ByteSize>().is_ok(),
+                _ => false,
+            };
+            if !valid {
+                self.report(
+                    Severity::Error,
+                    size.span(),
+                    format!(
+                        "invalid `size` of tool `{name}`, expecting a number of bytes \
+                        or a string such as \"300 MB\""
+                    ),
+                );
+            }
+        }
+
+        if let Some(env) = tool.get("env").and_then(Item::as_table_like) {
+            // These are only known after installation, so only check if the placeholders are valid.
+            let env_vars = Variables::default()
+                .with("install_dir", "")
+                .with("tool_dir", "");
+            for (_, value) in env.iter() {
+                let Some(value) = value.as_value() else {
+                    continue;
+                };
+                if let Some(Err(e)) = value.as_str().map(|raw| env_vars.expand(raw)) {
+                    self.report(Severity::Error, value.span(), e.to_string());
+                }
+            }
+        }
+
+        if let Some(deps) = tool.get("depends-on").and_then(Item::as_array) {
+            for dep in deps {
+                let Some(dep_name) = dep.as_str() else {
+                    continue;
+                };
+                if !known_tools.contains(dep_name) {
+                    self.report(
+                        Severity::Error,
+                        dep.span(),
+                        format!("tool `{name}` depends on `{dep_name}`, which is not defined for any target"),
+                    );
+                }
+            }
+        }
+
+        if let Some(conflicts) = tool.get("conflicts-with").and_then(Item::as_array) {
+            let deps = tool.get("depends-on").and_then(Item::as_array);
+            for conflict in conflicts {
+                let Some(conflict_name) = conflict.as_str() else {
+                    continue;
+                };
+                if !known_tools.contains(conflict_name) {
+                    self.report(
+                        Severity::Warning,
+                        conflict.span(),
+                        format!("tool `{name}` conflicts with `{conflict_name}`, which is not defined for any target"),
+                    );
+                }
+                if deps.is_some_and(|deps| deps.iter().any(|d| d.as_str() == Some(conflict_name))) {
+                    self.report(
+                        Severity::Error,
+                        conflict.span(),
+                        format!("tool `{name}` cannot both depend on and conflict with `{conflict_name}`"),
+                    );
+                }
+            }
+        }
+    }
+
+    fn variables(&self, target_key: &str) -> Variables {
+        Variables::new(target_key, &self.rust_version, self.version.as_deref())
+    }
+
+    /// Expand the `${...}` placeholders in a string `value`, report the expanded value as a note,
+    /// or the failure as an error.
+    ///
+    /// Return the expanded value, or `None` if it's not a string or cannot be expanded.
+    fn expand(&mut self, value: &toml_edit::Value, vars: &Variables, what: &str) -> Option<String> {
+        let raw = value.as_str()?;
+        if !raw.contains("${") {
+            return Some(raw.to_string());
+        }
+        match vars.expand(raw) {
+            Ok(expanded) => {
+                self.report(
+                    Severity::Note,
+                    value.span(),
+                    format!("{what} expands to `{expanded}`"),
+                );
+                Some(expanded)
+            }
+            Err(e) => {
+                self.report(Severity::Error, value.span(), e.to_string());
+                None
+            }
+        }
+    }
+
+    /// Report keys in `table` that are not in the `known` list.
+    fn check_keys(&mut self, table: &dyn TableLike, known: &[&str], context: &str) {
+        for (key, _) in table.iter() {
+            if !known.contains(&key) {
+                self.report(
+                    Severity::Error,
+                    table.key(key).and_then(|k| k.span()),
+                    format!("unknown key `{key}` in {context}"),
+                );
+            }
+        }
+    }
+
+    fn report(
+        &mut self,
+        severity: Severity,
+        span: Option<Range<usize>>,
+        message: impl Into<String>,
+    ) {
+        let position = span.map(|s| self.position(s.start));
+        self.diagnostics.push(Diagnostic {
+            severity,
+            message: message.into(),
+            position,
+        });
+    }
+
+    /// Convert a byte offset to the (1-based) line and column number.
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let before = &self.raw[..offset.min(self.raw.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let col = before[line_start..].chars().count() + 1;
+        (line, col)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint_str(input: &str) -> LintReport {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("toolset-manifest.toml");
+        std::fs::write(&path, input).unwrap();
+        lint_toolset_manifest(&path).unwrap()
+    }
+
+    #[test]
+    fn valid_manifest() {
+        let report = lint_str(
+            r#"
+[rust]
+version = "1.0.0"
+
+[tools.descriptions]
+a = "tool a"
+b = "tool b"
+
+[tools.group]
+Group = ["a", "b"]
+
+[presets]
+full = { description = "everything", components = ["a", "b"] }
+
+[tools.target.'cfg(unix)']
+a = { ver = "0.1.0", depends-on = ["b"] }
+b = { url = "https://example.com/b.tar.gz", version = "0.1.0" }
+"#,
+        );
+        assert!(report.diagnostics.is_empty(), "{report}");
+    }
+
+    #[test]
+    fn valid_overlay() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path().join("base.toml");
+        std::fs::write(&base, "[rust]\nversion = \"1.0.0\"\n").unwrap();
+        let overlay = root.path().join("toolset-manifest.toml");
+        std::fs::write(
+            &overlay,
+            r#"
+extends = "base.toml"
+
+[tools.descriptions]
+a = "tool a"
+
+[tools.target.'cfg(unix)']
+a = "0.1.0"
+"#,
+        )
+        .unwrap();
+        let report = lint_toolset_manifest(&overlay).unwrap();
+        assert!(report.diagnostics.is_empty(), "{report}");
+
+        // the composed manifest is still checked for required fields
+        std::fs::write(&base, "name = \"base\"\n").unwrap();
+        let report = lint_toolset_manifest(&overlay).unwrap();
+        assert_eq!(report.errors(), 1, "{report}");
+    }
+
+    #[test]
+    fn report_mistakes_with_positions() {
+        let report = lint_str(
+            r#"
+[rust]
+version = "1.0.0"
+componets = ["rustfmt"]
+
+[tools.descriptions]
+a = "tool a"
+b = "tool b"
+
+[tools.group]
+Group = ["a", "c"]
+
+[tools.target.x86_64-unknown-linux-gnu]
+a = { url = "https://example.com/a.tar.gz", depends-on = ["d"] }
+b = { path = "missing/b.tar.gz", branch = "main" }
+e = "0.1.0"
+"#,
+        );
+        let diagnostics = report
+            .diagnostics
+            .iter()
+            .map(|d| (d.severity, d.position.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                (Severity::Error, (4, 1)),
+                (Severity::Warning, (14, 1)),
+                (Severity::Error, (14, 59)),
+                (Severity::Error, (15, 34)),
+                (Severity::Error, (15, 14)),
+                (Severity::Warning, (16, 1)),
+                (Severity::Error, (11, 15)),
+            ],
+            "{report}"
+        );
+        assert_eq!(report.errors(), 5);
+        assert_eq!(report.warnings(), 2);
+    }
+
+    #[test]
+    fn report_ambiguous_tool_sources() {
+        let report = lint_str(
+            r#"
+[rust]
+version = "1.0.0"
+
+[tools.descriptions]
+a = "tool a"
+
+[tools.target.'cfg(not_a_cfg(unix))']
+a = { ver = "0.1.0", git = "https://example.com/a.git" }
+"#,
+        );
+        assert_eq!(report.errors(), 2, "{report}");
+        assert_eq!(report.diagnostics[0].position, Some((8, 15)));
+        assert_eq!(report.diagnostics[1].position, Some((9, 1)));
+    }
+
+    #[test]
+    fn report_expanded_values() {
+        let report = lint_str(
+            r#"
+[rust]
+version = "1.80.0"
+
+[tools.descriptions]
+a = "tool a"
+b = "tool b"
+
+[tools.target.x86_64-unknown-linux-gnu]
+a = { url = "https://example.com/a-${version}-${target}.tar.gz", version = "0.1.0" }
+b = { url = "https://example.com/b-${version}.tar.gz" }
+"#,
+        );
+        let diagnostics = report
+            .diagnostics
+            .iter()
+            .map(|d| (d.severity, d.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                (
+                    Severity::Note,
+                    "url of tool `a` expands to `https://example.com/a-0.1.0-x86_64-unknown-linux-gnu.tar.gz`"
+                ),
+                (
+                    Severity::Error,
+                    "unknown variable '${version}' in 'https://example.com/b-${version}.tar.gz'"
+                ),
+                (
+                    Severity::Warning,
+                    "tool `b` is installed from url but does not have a `version`"
+                ),
+            ],
+            "{report}"
+        );
+    }
+}
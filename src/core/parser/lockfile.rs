@@ -0,0 +1,397 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use url::Url;
+
+use super::fingerprint::{InstallationRecord, ToolRecord};
+use super::toolset_manifest::{ToolInfo, ToolsetManifest};
+use super::TomlParser;
+use crate::components::Component;
+use crate::utils::{self, HashAlgorithm};
+
+/// The exact versions and sources of everything in an installation,
+/// which is used to reproduce the same installation on another machine.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Lockfile {
+    /// Name of the toolkit.
+    pub name: Option<String>,
+    /// Version of the toolkit.
+    pub version: Option<String>,
+    pub rust: Option<LockedToolchain>,
+    #[serde(default)]
+    pub tools: BTreeMap<String, LockedTool>,
+}
+
+impl TomlParser for Lockfile {
+    const FILENAME: &str = "rim.lock";
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LockedToolchain {
+    pub version: String,
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub components: Vec<String>,
+    #[serde(default)]
+    pub targets: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct LockedTool {
+    pub version: Option<String>,
+    /// The url that the package of this tool was downloaded from.
+    pub url: Option<Url>,
+    pub git: Option<Url>,
+    pub rev: Option<String>,
+    pub sha256: Option<String>,
+    pub sha512: Option<String>,
+    /// The license that was accepted for this tool.
+    pub license: Option<String>,
+}
+
+impl LockedTool {
+    /// Lock a tool installed as `record`, using the git commit and package checksum resolved
+    /// when installing it, or the ones declared in its `info` from the manifest otherwise.
+    fn new(record: &ToolRecord, version: Option<&str>, info: Option<&ToolInfo>) -> Self {
+        let mut locked = Self {
+            version: version.map(ToOwned::to_owned),
+            ..Default::default()
+        };
+        if let Some(info) = info {
+            if let ToolInfo::Git { git, rev, .. } = info {
+                locked.git = Some(git.clone());
+                locked.rev = rev.clone();
+            }
+            for (algorithm, sum) in info.checksums() {
+                match algorithm {
+                    HashAlgorithm::Sha256 => locked.sha256 = Some(sum.to_string()),
+                    HashAlgorithm::Sha512 => locked.sha512 = Some(sum.to_string()),
+                }
+            }
+            locked.url = source_urls(info).first().cloned();
+        }
+        // prefer the commit, checksum and mirror that were actually used
+        if record.rev.is_some() {
+            locked.rev = record.rev.clone();
+        }
+        if record.sha256.is_some() {
+            locked.sha256 = record.sha256.clone();
+        }
+        if record.url.is_some() {
+            locked.url = record.url.clone();
+        }
+        locked
+    }
+
+    /// Check if the tool `info` from a manifest still matches this locked tool,
+    /// return the reason if it doesn't.
+    fn mismatch(&self, info: &ToolInfo) -> Option<String> {
+        if !info.is_cargo_tool() && info.version() != self.version.as_deref() {
+            return Some(format!(
+                "version '{}' was locked, but got '{}'",
+                self.version.as_deref().unwrap_or("none"),
+                info.version().unwrap_or("none")
+            ));
+        }
+        if let Some(url) = &self.url {
+            let urls = source_urls(info);
+            if !urls.is_empty() && !urls.contains(url) {
+                return Some(format!("'{url}' is no longer a source of it"));
+            }
+        }
+        let (git, rev) = match info {
+            ToolInfo::Git { git, rev, .. } => (Some(git), rev.as_deref()),
+            _ => (None, None),
+        };
+        // the locked revision is the full commit that a branch or tag was resolved to
+        let rev_changed = rev.is_some_and(|rev| {
+            !self
+                .rev
+                .as_deref()
+                .is_some_and(|locked| locked.starts_with(rev))
+        });
+        if self.git.as_ref() != git || rev_changed {
+            return Some("the git repository or revision has changed".into());
+        }
+        let changed = info.checksums().into_iter().any(|(algorithm, sum)| {
+            let locked = match algorithm {
+                HashAlgorithm::Sha256 => self.sha256.as_deref(),
+                HashAlgorithm::Sha512 => self.sha512.as_deref(),
+            };
+            locked.is_some_and(|locked| !locked.eq_ignore_ascii_case(sum))
+        });
+        if changed {
+            return Some("the checksum of its package has changed".into());
+        }
+        None
+    }
+}
+
+impl Lockfile {
+    /// Collect the exact versions and sources of everything installed in `record`,
+    /// the sources are taken from the `manifest` that the installation was made with.
+    pub fn from_installation(record: &InstallationRecord, manifest: &ToolsetManifest) -> Self {
+        let tools_info = manifest.current_target_tools().unwrap_or_default();
+        let rust = record.rust.as_ref().map(|rust| LockedToolchain {
+            version: record
+                .installed_toolchain()
+                .map(|(ver, _)| ver.to_string())
+                .unwrap_or_default(),
+            profile: manifest.toolchain_profile().map(|p| p.name.clone()),
+            components: rust.components.clone(),
+            targets: rust.targets.clone(),
+        });
+        let tools = record
+            .tools
+            .iter()
+            .map(|(name, tool)| {
+                let info = tools_info.get(name);
+                let mut locked = LockedTool::new(tool, record.get_tool_version(name), info);
+                locked.license = record
+                    .accepted_licenses
+                    .get(name)
+                    .filter(|_| info.and_then(ToolInfo::license).is_some())
+                    .cloned();
+                (name.clone(), locked)
+            })
+            .collect();
+
+        Self {
+            name: record.name.clone(),
+            version: record.version.clone(),
+            rust,
+            tools,
+        }
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        let content = self.to_toml().context("unable to serialize lockfile")?;
+        utils::write_file(path, &content, false)
+    }
+
+    /// Get the components of `manifest` that were locked, failing if any of them
+    /// no longer matches the manifest.
+    ///
+    /// The versions of the tools installed using `cargo` are pinned to the locked ones.
+    pub fn components_from(&self, manifest: &ToolsetManifest) -> Result<Vec<Component>> {
+        if let Some(rust) = &self.rust {
+            if rust.version != manifest.rust_version() {
+                bail!(
+                    "the locked rust toolchain '{}' does not match the one in toolkit: '{}'",
+                    rust.version,
+                    manifest.rust_version()
+                );
+            }
+        }
+        let locked_tc = |comp: &Component| {
+            self.rust.as_ref().is_some_and(|rust| {
+                let list = if comp.is_target {
+                    &rust.targets
+                } else {
+                    &rust.components
+                };
+                list.contains(&comp.name)
+            })
+        };
+
+        let mut components: Vec<Component> = vec![];
+        for mut comp in manifest.current_target_components(true)? {
+            if comp.is_rust_toolchain() {
+                components.push(comp);
+                continue;
+            }
+            if comp.is_toolchain_component {
+                if locked_tc(&comp) {
+                    components.push(comp);
+                }
+                continue;
+            }
+            let Some(locked) = self.tools.get(&comp.name) else {
+                continue;
+            };
+            let Some(info) = comp.tool_installer.as_mut() else {
+                continue;
+            };
+            if let Some(reason) = locked.mismatch(info) {
+                bail!("'{}' no longer matches the lockfile: {reason}", comp.name);
+            }
+            if let (Some(version), true) = (&locked.version, info.is_cargo_tool()) {
+                pin_version(info, version);
+                comp.version = Some(version.clone());
+            }
+            pin_artifacts(info, locked);
+            components.push(comp);
+        }
+
+        let locked_names = self.tools.keys().chain(
+            self.rust
+                .iter()
+                .flat_map(|rust| rust.components.iter().chain(&rust.targets)),
+        );
+        for name in locked_names {
+            if !components.iter().any(|c| &c.name == name) {
+                bail!("'{name}' in the lockfile is not available in the toolkit");
+            }
+        }
+        Ok(components)
+    }
+}
+
+/// Get the urls that the package of a tool could be downloaded from.
+fn source_urls(info: &ToolInfo) -> &[Url] {
+    match info {
+        ToolInfo::Url { url, .. } => url.urls(),
+        _ => &[],
+    }
+}
+
+fn pin_version(info: &mut ToolInfo, version: &str) {
+    match info {
+        ToolInfo::PlainVersion(ver) | ToolInfo::DetailedVersion { ver, .. } => {
+            *ver = version.to_string();
+        }
+        _ => (),
+    }
+}
+
+/// Make the installation of a tool use the locked git commit, and verify its package
+/// against the locked checksums.
+fn pin_artifacts(info: &mut ToolInfo, locked: &LockedTool) {
+    match info {
+        ToolInfo::Git {
+            branch, tag, rev, ..
+        } => {
+            if let Some(locked_rev) = &locked.rev {
+                *branch = None;
+                *tag = None;
+                *rev = Some(locked_rev.clone());
+            }
+        }
+        ToolInfo::Path { sha256, sha512, .. } | ToolInfo::Url { sha256, sha512, .. } => {
+            if locked.sha256.is_some() {
+                sha256.clone_from(&locked.sha256);
+            }
+            if locked.sha512.is_some() {
+                sha512.clone_from(&locked.sha512);
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> ToolsetManifest {
+        let input = format!(
+            r#"
+version = "0.2.0"
+
+[rust]
+version = "1.80.0"
+optional-components = ["clippy"]
+targets = []
+
+[tools.target.{target}]
+aaa = "^1"
+bbb = {{ url = "https://example.com/bbb.zip", version = "2.0.0", sha256 = "abcd" }}
+ccc = {{ url = "https://example.com/ccc.zip", version = "3.0.0", optional = true }}
+ddd = {{ git = "https://example.com/ddd.git", branch = "main", optional = true }}
+"#,
+            target = env!("TARGET")
+        );
+        ToolsetManifest::from_str(&input).unwrap()
+    }
+
+    fn record() -> InstallationRecord {
+        let input = r#"
+name = "Toolkit"
+version = "0.2.0"
+root = '/path/to/something'
+
+[rust]
+version = "1.80.0"
+components = ["clippy"]
+
+[tools]
+aaa = { kind = "cargo-tool", version = "1.2.3", paths = [] }
+bbb = { kind = "custom", version = "2.0.0", paths = [], url = "https://example.com/bbb.zip" }
+"#;
+        InstallationRecord::from_str(input).unwrap()
+    }
+
+    #[test]
+    fn export_and_reproduce() {
+        let lock = Lockfile::from_installation(&record(), &manifest());
+        assert_eq!(lock.rust.as_ref().unwrap().version, "1.80.0");
+        assert_eq!(lock.tools["aaa"].version.as_deref(), Some("1.2.3"));
+        assert_eq!(lock.tools["bbb"].sha256.as_deref(), Some("abcd"));
+
+        let lock = Lockfile::from_str(&lock.to_toml().unwrap()).unwrap();
+        let components = lock.components_from(&manifest()).unwrap();
+        let names: Vec<&str> = components.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names[1..], ["clippy", "aaa", "bbb"]);
+        let aaa = components.iter().find(|c| c.name == "aaa").unwrap();
+        assert_eq!(
+            aaa.tool_installer.as_ref().unwrap().version(),
+            Some("1.2.3")
+        );
+    }
+
+    #[test]
+    fn pin_resolved_artifacts() {
+        let input = r#"
+name = "Toolkit"
+version = "0.2.0"
+root = '/path/to/something'
+
+[tools]
+ccc = { kind = "custom", version = "3.0.0", paths = [], url = "https://example.com/ccc.zip", sha256 = "ef01" }
+ddd = { kind = "cargo-tool", paths = [], rev = "0123abcd" }
+"#;
+        let record = InstallationRecord::from_str(input).unwrap();
+        let lock = Lockfile::from_installation(&record, &manifest());
+        assert_eq!(lock.tools["ccc"].sha256.as_deref(), Some("ef01"));
+        assert_eq!(lock.tools["ddd"].rev.as_deref(), Some("0123abcd"));
+
+        let components = lock.components_from(&manifest()).unwrap();
+        let info = |name: &str| {
+            components
+                .iter()
+                .find(|c| c.name == name)
+                .and_then(|c| c.tool_installer.clone())
+                .unwrap()
+        };
+        assert_eq!(info("ccc").checksums(), [(HashAlgorithm::Sha256, "ef01")]);
+        let ToolInfo::Git {
+            branch, tag, rev, ..
+        } = info("ddd")
+        else {
+            panic!("'ddd' should be installed from git");
+        };
+        assert_eq!((branch, tag), (None, None));
+        assert_eq!(rev.as_deref(), Some("0123abcd"));
+    }
+
+    #[test]
+    fn mismatched_artifacts() {
+        let mut lock = Lockfile::from_installation(&record(), &manifest());
+        lock.tools.get_mut("bbb").unwrap().sha256 = Some("changed".into());
+        let err = lock.components_from(&manifest()).unwrap_err();
+        assert!(err.to_string().contains("'bbb' no longer matches"));
+
+        let mut lock = Lockfile::from_installation(&record(), &manifest());
+        lock.rust.as_mut().unwrap().version = "1.79.0".into();
+        assert!(lock.components_from(&manifest()).is_err());
+
+        let mut lock = Lockfile::from_installation(&record(), &manifest());
+        lock.tools.insert("ddd".into(), LockedTool::default());
+        let err = lock.components_from(&manifest()).unwrap_err();
+        assert!(err.to_string().contains("'ddd'"));
+    }
+}
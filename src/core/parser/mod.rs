@@ -1,6 +1,9 @@
 pub(crate) mod cargo_config;
 pub mod dist_manifest;
 pub mod fingerprint;
+pub mod installed_files;
+pub mod lint;
+pub mod lockfile;
 pub(crate) mod release_info;
 pub mod toolset_manifest;
 
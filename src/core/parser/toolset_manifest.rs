@@ -2,21 +2,28 @@
 //! such as its name, version, and what's included etc.
 
 use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::{collections::BTreeMap, path::PathBuf};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use cfg_expr::targets::get_builtin_target_by_triple;
+use cfg_expr::{Expression, Predicate};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
 use url::Url;
 
 use crate::components::Component;
-use crate::core::custom_instructions;
-use crate::utils;
+use crate::core::update::ensure_manager_version;
+use crate::core::{custom_instructions, registry, signature};
+use crate::utils::{self, HashAlgorithm};
 
 use super::TomlParser;
 
 /// A map of tools, contains the name and source package information.
 pub type ToolMap = IndexMap<String, ToolInfo>;
+/// A map of `(target, tool name)` to the directory of the manifest that declared the tool.
+type ToolOrigins = HashMap<(String, String), PathBuf>;
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Default, Clone)]
 #[serde(rename_all = "kebab-case")]
@@ -25,15 +32,26 @@ pub struct ToolsetManifest {
     pub name: Option<String>,
     /// Product version to be cached after installation, so that we can show it as `installed`
     pub version: Option<String>,
+    /// The minimum version of manager that is able to handle this manifest.
+    pub min_manager_version: Option<String>,
 
     pub(crate) rust: RustToolchain,
     #[serde(default)]
     pub(crate) tools: Tools,
+    /// Named sets of components that can be selected at once, such as `minimal` or `full`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    presets: BTreeMap<String, Preset>,
     /// Proxy settings that used for download.
     pub proxy: Option<Proxy>,
     /// Path to the manifest file.
     #[serde(skip)]
     path: Option<PathBuf>,
+    /// Directories of the manifests that declared each tool, keyed by `(target, tool name)`.
+    ///
+    /// This is only recorded for manifests that `extends` another, so that the relative paths
+    /// of tools can be resolved from the manifest where they were declared.
+    #[serde(skip)]
+    origins: ToolOrigins,
 }
 
 impl TomlParser for ToolsetManifest {
@@ -41,7 +59,8 @@ impl TomlParser for ToolsetManifest {
 
     fn load<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
         let raw = utils::read_to_string("manifest", &path)?;
-        let mut temp_manifest = Self::from_str(&raw)?;
+        let source = ManifestSource::Path(path.as_ref().to_path_buf());
+        let mut temp_manifest = Self::compose(&raw, &source, false)?;
         temp_manifest.path = Some(path.as_ref().to_path_buf());
         Ok(temp_manifest)
     }
@@ -62,8 +81,16 @@ impl ToolsetManifest {
         self.rust.optional_components.as_slice()
     }
 
+    /// Get a list of all optional toolchain targets.
+    pub fn optional_toolchain_targets(&self) -> &[String] {
+        self.rust.optional_targets.as_slice()
+    }
+
     pub fn get_tool_description(&self, toolname: &str) -> Option<&str> {
-        self.tools.descriptions.get(toolname).map(|s| s.as_str())
+        self.tools
+            .descriptions
+            .get(toolname)
+            .map(LocalizedText::get)
     }
 
     /// Get the group name of a certain tool, if exist.
@@ -71,11 +98,59 @@ impl ToolsetManifest {
         self.tools
             .group
             .iter()
-            .find_map(|(group, tools)| tools.contains(toolname).then_some(group.as_str()))
+            .find_map(|(group, tools)| tools.members().contains(toolname).then_some(group.as_str()))
+    }
+
+    /// Get the names of tools that cannot be installed along with `toolname`,
+    /// which are the ones it `conflicts-with` (or being conflicted with),
+    /// and the other members of the "choose one" groups it belongs to.
+    pub fn conflicts_of(&self, toolname: &str, tools: &ToolMap) -> Vec<String> {
+        let mut conflicts: Vec<String> = vec![];
+        let mut add = |name: &str| {
+            if name != toolname && !conflicts.iter().any(|c| c == name) {
+                conflicts.push(name.to_string());
+            }
+        };
+        if let Some(info) = tools.get(toolname) {
+            info.conflicts().iter().for_each(|name| add(name));
+        }
+        for (name, info) in tools {
+            if info.conflicts().iter().any(|c| c == toolname) {
+                add(name);
+            }
+        }
+        for group in self.tools.group.values() {
+            if group.is_choose_one() && group.members().contains(toolname) {
+                let mut members = group.members().iter().collect::<Vec<_>>();
+                members.sort();
+                members.into_iter().for_each(|name| add(name));
+            }
+        }
+        conflicts
+    }
+
+    /// Get all the presets declared in the manifest, keyed by their names.
+    pub fn presets(&self) -> &BTreeMap<String, Preset> {
+        &self.presets
+    }
+
+    /// Get a preset by its name, fails if there's no such preset.
+    pub fn preset(&self, name: &str) -> Result<&Preset> {
+        self.presets.get(name).ok_or_else(|| {
+            let available = self.presets.keys().cloned().collect::<Vec<_>>();
+            anyhow!(
+                "no preset named '{name}' in the manifest, available presets: [{}]",
+                available.join(", ")
+            )
+        })
     }
 
     pub fn toolchain_group_name(&self) -> &str {
-        self.rust.name.as_deref().unwrap_or("Rust Toolchain")
+        self.rust
+            .name
+            .as_ref()
+            .map(LocalizedText::get)
+            .unwrap_or("Rust Toolchain")
     }
 
     pub fn toolchain_profile(&self) -> Option<&ToolchainProfile> {
@@ -104,13 +179,50 @@ impl ToolsetManifest {
     }
 
     /// Get the tools that are only available in current target.
-    pub fn current_target_tools(&self) -> Option<&ToolMap> {
-        let cur_target = env!("TARGET");
-        self.tools.target.get(cur_target)
+    ///
+    /// Check [`tools_for_target`](Self::tools_for_target) for how the tools are collected.
+    pub fn current_target_tools(&self) -> Option<ToolMap> {
+        self.tools_for_target(env!("TARGET"))
+    }
+
+    /// Get the tools that are available in a specific target `triple`, which are merged from
+    /// the section keyed by this exact triple, and every section keyed by a matching
+    /// `cfg(...)` predicate, such as `cfg(unix)` or `cfg(all(target_os = "linux", target_arch = "x86_64"))`.
+    ///
+    /// When a tool is defined in multiple sections, the one in the exact triple section wins,
+    /// otherwise the one in the first matching `cfg` section (in alphabetical order) is used.
+    ///
+    /// Return `None` if there are no sections matching the target.
+    pub fn tools_for_target(&self, triple: &str) -> Option<ToolMap> {
+        let mut tools = self.tools.target.get(triple).cloned();
+        for (key, map) in &self.tools.target {
+            if !cfg_matches_target(key, triple) {
+                continue;
+            }
+            let merged = tools.get_or_insert_with(ToolMap::new);
+            for (name, info) in map {
+                if !merged.contains_key(name) {
+                    merged.insert(name.clone(), info.clone());
+                }
+            }
+        }
+        tools
+    }
+
+    /// Get the names of every tool declared in any target.
+    pub(crate) fn all_tool_names(&self) -> HashSet<&str> {
+        self.tools
+            .target
+            .values()
+            .flat_map(|map| map.keys().map(|name| name.as_str()))
+            .collect()
     }
 
     /// Get the mut reference to the tools that are only available in current target.
     ///
+    /// Note that unlike [`current_target_tools`](Self::current_target_tools), this only
+    /// returns the section keyed by the exact target triple, without any `cfg(...)` sections.
+    ///
     /// Return `None` if there are no available tools in the current target.
     pub fn current_target_tools_mut(&mut self) -> Option<&mut ToolMap> {
         let cur_target = env!("TARGET");
@@ -130,7 +242,11 @@ impl ToolsetManifest {
         // Add a component that represents rust toolchain
         let mut components = vec![Component::new(
             profile_name,
-            profile.description.as_deref().unwrap_or_default(),
+            profile
+                .description
+                .as_ref()
+                .map(LocalizedText::get)
+                .unwrap_or_default(),
         )
         .group_name(Some(self.toolchain_group_name()))
         .is_toolchain_component(true)
@@ -151,6 +267,20 @@ impl ToolsetManifest {
             );
         }
 
+        for target in self.optional_toolchain_targets() {
+            components.push(
+                Component::new(
+                    target,
+                    self.get_tool_description(target).unwrap_or_default(),
+                )
+                .group_name(Some(self.toolchain_group_name()))
+                .optional(true)
+                .is_toolchain_component(true)
+                .is_target(true)
+                .version(Some(tc_channel)),
+            );
+        }
+
         if let Some(tools) = self.current_target_tools() {
             let installed_in_env = if fresh_install {
                 // components that are already installed in user's machine, such as vscode, or mingw.
@@ -159,8 +289,8 @@ impl ToolsetManifest {
                 vec![]
             };
 
-            for (tool_name, tool_info) in tools {
-                let installed = installed_in_env.contains(&tool_name);
+            for (tool_name, tool_info) in &tools {
+                let installed = installed_in_env.contains(tool_name);
                 let version = if fresh_install && installed {
                     // if the tool is already installed but we are doing a fresh install here,
                     // which means it was installed by user not by `rim`,
@@ -175,6 +305,7 @@ impl ToolsetManifest {
                         self.get_tool_description(tool_name).unwrap_or_default(),
                     )
                     .group_name(self.group_name(tool_name))
+                    .conflicts(self.conflicts_of(tool_name, &tools))
                     .tool_installer(tool_info)
                     .required(tool_info.is_required())
                     .optional(tool_info.is_optional())
@@ -188,11 +319,11 @@ impl ToolsetManifest {
     }
 
     /// Get a list of tool names if those are already installed in current target.
-    pub fn already_installed_tools(&self) -> Vec<&String> {
+    pub fn already_installed_tools(&self) -> Vec<String> {
         let Some(map) = self.current_target_tools() else {
             return vec![];
         };
-        map.keys()
+        map.into_keys()
             .filter(|name| custom_instructions::is_installed(name))
             .collect()
     }
@@ -221,9 +352,10 @@ impl ToolsetManifest {
     /// Turn all the relative paths in the `tools` section to some absolute paths.
     ///
     /// There are some rules applied when converting, including:
-    /// 1. If the manifest was loaded from a path,
+    /// 1. If the manifest `extends` another, each path will be combined with the directory of the manifest declaring it.
+    /// 2. If the manifest was loaded from a path,
     ///     all relative paths will be forced to combine with the path loading from.
-    /// 2. If the manifest was not loaded from path,
+    /// 3. If the manifest was not loaded from path,
     ///     all relative paths will be forced to combine with the parent directory of this executable.
     ///     (Assuming the manifest was baked in the executable)
     ///
@@ -233,10 +365,17 @@ impl ToolsetManifest {
     pub fn adjust_paths(&mut self) -> Result<()> {
         let parent_dir = self.parent_dir()?;
 
-        for tool in self.tools.target.values_mut() {
-            for tool_info in tool.values_mut() {
+        for (target, tool) in &mut self.tools.target {
+            for (name, tool_info) in tool {
+                let root = self
+                    .origins
+                    .get(&(target.clone(), name.clone()))
+                    .unwrap_or(&parent_dir);
                 if let ToolInfo::Path { path, .. } = tool_info {
-                    *path = utils::to_nomalized_abspath(path.as_path(), Some(&parent_dir))?;
+                    *path = utils::to_nomalized_abspath(path.as_path(), Some(root))?;
+                }
+                if let Some(path) = tool_info.license_mut().and_then(|l| l.path.as_mut()) {
+                    *path = utils::to_nomalized_abspath(path.as_path(), Some(root))?;
                 }
             }
         }
@@ -289,9 +428,15 @@ pub(crate) struct RustToolchain {
     /// Optional components are only installed if user choose to.
     #[serde(default)]
     pub(crate) optional_components: Vec<String>,
+    /// Additional targets (for cross compilation) that are installed by default.
+    #[serde(default)]
+    pub(crate) targets: Vec<String>,
+    /// Additional targets that are only installed if user choose to.
+    #[serde(default)]
+    pub(crate) optional_targets: Vec<String>,
     /// Specifies a verbose name if this was provided.
     #[serde(alias = "group")]
-    pub(crate) name: Option<String>,
+    pub(crate) name: Option<LocalizedText>,
     /// File [`Url`] to install rust toolchain.
     offline_dist_server: Option<String>,
     /// Contains target specific `rustup-init` binaries.
@@ -314,7 +459,7 @@ impl RustToolchain {
 pub struct ToolchainProfile {
     pub name: String,
     pub verbose_name: Option<String>,
-    pub description: Option<String>,
+    pub description: Option<LocalizedText>,
 }
 
 impl Default for ToolchainProfile {
@@ -339,16 +484,214 @@ impl From<&str> for ToolchainProfile {
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Default, Clone)]
 pub(crate) struct Tools {
     #[serde(default)]
-    descriptions: BTreeMap<String, String>,
+    descriptions: BTreeMap<String, LocalizedText>,
     /// Containing groups of tools.
     ///
     /// Note that not all tools will have a group.
     #[serde(default)]
-    group: BTreeMap<String, HashSet<String>>,
+    group: BTreeMap<String, ToolGroup>,
+    /// Tools for each target, keyed by either an exact target triple,
+    /// or a `cfg(...)` predicate that could match multiple targets.
     #[serde(default)]
     target: BTreeMap<String, ToolMap>,
 }
 
+/// A text that is either the same in every language, or translated into several locales,
+/// such as `{ en = "...", zh-CN = "..." }`.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+pub enum LocalizedText {
+    Plain(String),
+    Localized(BTreeMap<String, String>),
+}
+
+impl LocalizedText {
+    /// Get the text in the active locale, check [`get_for`](Self::get_for) for the fallbacks.
+    pub fn get(&self) -> &str {
+        self.get_for(&rust_i18n::locale())
+    }
+
+    /// Get the text in `locale`, if there's no such translation, fallback to the one
+    /// in the same language (such as `zh` for `zh-CN`), then `en`, then whatever is available.
+    pub fn get_for(&self, locale: &str) -> &str {
+        let translations = match self {
+            Self::Plain(text) => return text,
+            Self::Localized(translations) => translations,
+        };
+        // `zh_CN` and `zh-cn` are treated the same as `zh-CN`
+        let normalize = |loc: &str| loc.replace('_', "-").to_lowercase();
+        let language = |loc: &str| normalize(loc).split('-').next().map(ToOwned::to_owned);
+        let find = |matches: &dyn Fn(&str) -> bool| {
+            translations
+                .iter()
+                .find_map(|(loc, text)| matches(loc).then_some(text.as_str()))
+        };
+
+        find(&|loc| normalize(loc) == normalize(locale))
+            .or_else(|| find(&|loc| language(loc) == language(locale)))
+            .or_else(|| find(&|loc| normalize(loc) == "en"))
+            .or_else(|| translations.values().next().map(String::as_str))
+            .unwrap_or_default()
+    }
+}
+
+impl From<&str> for LocalizedText {
+    fn from(value: &str) -> Self {
+        Self::Plain(value.to_string())
+    }
+}
+
+/// A named set of components, which is a short-cut of selecting components one by one.
+///
+/// Required components are always selected, thus they don't need to be listed in a preset.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Default, Clone)]
+pub struct Preset {
+    pub description: Option<String>,
+    /// Names of the components (tools, optional toolchain components or targets) in this preset.
+    #[serde(default)]
+    pub components: Vec<String>,
+}
+
+impl Preset {
+    pub fn contains(&self, component: &str) -> bool {
+        self.components.iter().any(|c| c == component)
+    }
+}
+
+/// Members of a group of tools, which is either a plain list of tool names,
+/// or a table with `members` and a `choose-one` flag, meaning that
+/// at most one of the members could be installed.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+pub(crate) enum ToolGroup {
+    Members(HashSet<String>),
+    Detailed {
+        members: HashSet<String>,
+        #[serde(default, rename = "choose-one")]
+        choose_one: bool,
+    },
+}
+
+impl ToolGroup {
+    pub(crate) fn members(&self) -> &HashSet<String> {
+        match self {
+            Self::Members(members) | Self::Detailed { members, .. } => members,
+        }
+    }
+
+    pub(crate) fn is_choose_one(&self) -> bool {
+        matches!(
+            self,
+            Self::Detailed {
+                choose_one: true,
+                ..
+            }
+        )
+    }
+}
+
+/// Check if a `cfg(...)` target key matches the given target triple.
+///
+/// Return `false` if the key is not a `cfg` expression, or it cannot be parsed,
+/// or the target triple is unknown.
+pub(crate) fn cfg_matches_target(key: &str, triple: &str) -> bool {
+    if !key.starts_with("cfg(") {
+        return false;
+    }
+    let (Ok(expr), Some(target)) = (Expression::parse(key), get_builtin_target_by_triple(triple))
+    else {
+        return false;
+    };
+    expr.eval(|pred| match pred {
+        Predicate::Target(tp) => tp.matches(target),
+        _ => false,
+    })
+}
+
+/// The `${version}` placeholder that is kept in the `prebuilt` archives of cargo tools
+/// whose versions are requirements, check [`Mirrors::with_version`].
+pub(crate) const VERSION_PLACEHOLDER: &str = "${version}";
+
+/// Values of the `${...}` placeholders that can be used in the urls and paths of a manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Variables(HashMap<&'static str, String>);
+
+impl Variables {
+    /// Create the variables available to the given target key,
+    /// which is either an exact target triple, or a `cfg(...)` predicate.
+    ///
+    /// Since a `cfg(...)` predicate could match multiple targets,
+    /// `${target}`, `${os}` and `${arch}` will be the ones of current target in that case.
+    pub(crate) fn new(target_key: &str, rust_version: &str, version: Option<&str>) -> Self {
+        let triple = if target_key.starts_with("cfg(") {
+            env!("TARGET")
+        } else {
+            target_key
+        };
+        let mut vars = HashMap::from([
+            ("target", triple.to_string()),
+            ("rust.version", rust_version.to_string()),
+        ]);
+        if let Some(ver) = version {
+            vars.insert("version", ver.to_string());
+        }
+        if let Some(info) = get_builtin_target_by_triple(triple) {
+            vars.insert("arch", info.arch.to_string());
+            if let Some(os) = &info.os {
+                vars.insert("os", os.to_string());
+            }
+        }
+        Self(vars)
+    }
+
+    /// Add a variable, or override its value if it already exists.
+    pub(crate) fn with(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.0.insert(name, value.into());
+        self
+    }
+
+    /// Override the value of `${version}`, such as using the version of a certain tool.
+    pub(crate) fn with_version(mut self, version: Option<&str>) -> Self {
+        if let Some(ver) = version {
+            self.0.insert("version", ver.to_string());
+        }
+        self
+    }
+
+    /// Keep `${version}` as is if the `version` of a cargo tool is a requirement (such as `^0.9`),
+    /// so that it can be replaced with the resolved version when installing.
+    pub(crate) fn keep_unresolved_version(self, version: Option<&str>) -> Self {
+        if version.and_then(registry::version_req).is_some() {
+            self.with("version", VERSION_PLACEHOLDER)
+        } else {
+            self
+        }
+    }
+
+    /// Replace every `${name}` placeholder in `input` with its value.
+    ///
+    /// # Errors
+    /// Return `Err` if a placeholder is not closed, or its variable is unknown.
+    pub(crate) fn expand(&self, input: &str) -> Result<String> {
+        let mut expanded = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(start) = rest.find("${") {
+            expanded.push_str(&rest[..start]);
+            let Some(len) = rest[start..].find('}') else {
+                bail!("unclosed placeholder in '{input}'");
+            };
+            let name = &rest[start + 2..start + len];
+            let Some(value) = self.0.get(name) else {
+                bail!("unknown variable '${{{name}}}' in '{input}'");
+            };
+            expanded.push_str(value);
+            rest = &rest[start + len + 1..];
+        }
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+}
+
 impl Tools {
     #[allow(unused)]
     pub(crate) fn new<I>(targeted_tools: I) -> Tools
@@ -374,6 +717,28 @@ pub enum ToolInfo {
         required: bool,
         #[serde(default)]
         optional: bool,
+        /// Names of other tools that need to be installed before this one.
+        #[serde(default, rename = "depends-on", skip_serializing_if = "Vec::is_empty")]
+        depends_on: Vec<String>,
+        /// Names of other tools that cannot be installed along with this one.
+        #[serde(
+            default,
+            rename = "conflicts-with",
+            skip_serializing_if = "Vec::is_empty"
+        )]
+        conflicts_with: Vec<String>,
+        /// A license that must be accepted by the user before installing this tool.
+        license: Option<License>,
+        /// Estimated size of this tool after installation, used to check the free disk space.
+        size: Option<ByteSize>,
+        /// Environment variables to set after installing this tool, the values could contain
+        /// `${install_dir}` and `${tool_dir}` placeholders.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        env: BTreeMap<String, String>,
+        #[serde(flatten)]
+        hooks: Hooks,
+        #[serde(flatten)]
+        cargo: CargoOptions,
     },
     Git {
         git: Url,
@@ -384,25 +749,308 @@ pub enum ToolInfo {
         required: bool,
         #[serde(default)]
         optional: bool,
+        /// Names of other tools that need to be installed before this one.
+        #[serde(default, rename = "depends-on", skip_serializing_if = "Vec::is_empty")]
+        depends_on: Vec<String>,
+        /// Names of other tools that cannot be installed along with this one.
+        #[serde(
+            default,
+            rename = "conflicts-with",
+            skip_serializing_if = "Vec::is_empty"
+        )]
+        conflicts_with: Vec<String>,
+        /// A license that must be accepted by the user before installing this tool.
+        license: Option<License>,
+        /// Estimated size of this tool after installation, used to check the free disk space.
+        size: Option<ByteSize>,
+        /// Environment variables to set after installing this tool, the values could contain
+        /// `${install_dir}` and `${tool_dir}` placeholders.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        env: BTreeMap<String, String>,
+        #[serde(flatten)]
+        hooks: Hooks,
+        #[serde(flatten)]
+        cargo: CargoOptions,
     },
     Path {
         path: PathBuf,
         version: Option<String>,
+        /// Expected SHA-256 checksum of the package, verified before installation.
+        sha256: Option<String>,
+        /// Expected SHA-512 checksum of the package, verified before installation.
+        sha512: Option<String>,
         #[serde(default)]
         required: bool,
         #[serde(default)]
         optional: bool,
+        /// Names of other tools that need to be installed before this one.
+        #[serde(default, rename = "depends-on", skip_serializing_if = "Vec::is_empty")]
+        depends_on: Vec<String>,
+        /// Names of other tools that cannot be installed along with this one.
+        #[serde(
+            default,
+            rename = "conflicts-with",
+            skip_serializing_if = "Vec::is_empty"
+        )]
+        conflicts_with: Vec<String>,
+        /// A license that must be accepted by the user before installing this tool.
+        license: Option<License>,
+        /// Estimated size of this tool after installation, used to check the free disk space.
+        size: Option<ByteSize>,
+        /// Environment variables to set after installing this tool, the values could contain
+        /// `${install_dir}` and `${tool_dir}` placeholders.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        env: BTreeMap<String, String>,
+        #[serde(flatten)]
+        hooks: Hooks,
     },
     Url {
-        url: Url,
+        url: Mirrors,
         version: Option<String>,
+        /// Expected SHA-256 checksum of the downloaded package, verified before installation.
+        sha256: Option<String>,
+        /// Expected SHA-512 checksum of the downloaded package, verified before installation.
+        sha512: Option<String>,
         #[serde(default)]
         required: bool,
         #[serde(default)]
         optional: bool,
+        /// Names of other tools that need to be installed before this one.
+        #[serde(default, rename = "depends-on", skip_serializing_if = "Vec::is_empty")]
+        depends_on: Vec<String>,
+        /// Names of other tools that cannot be installed along with this one.
+        #[serde(
+            default,
+            rename = "conflicts-with",
+            skip_serializing_if = "Vec::is_empty"
+        )]
+        conflicts_with: Vec<String>,
+        /// A license that must be accepted by the user before installing this tool.
+        license: Option<License>,
+        /// Estimated size of this tool after installation, used to check the free disk space.
+        size: Option<ByteSize>,
+        /// Environment variables to set after installing this tool, the values could contain
+        /// `${install_dir}` and `${tool_dir}` placeholders.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        env: BTreeMap<String, String>,
+        #[serde(flatten)]
+        hooks: Hooks,
     },
 }
 
+/// Commands to run at certain stages of a tool's (un)installation.
+///
+/// Each command is a list containing the program and its arguments, such as `["tool", "--init"]`.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub struct Hooks {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_install: Vec<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_uninstall: Vec<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_uninstall: Vec<Vec<String>>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.post_install.is_empty()
+            && self.pre_uninstall.is_empty()
+            && self.post_uninstall.is_empty()
+    }
+
+    /// Describe the `commands` of a tool at certain `stage` in human readable lines,
+    /// such as `my-tool (post-install): tool --init`.
+    pub fn describe(name: &str, stage: &str, commands: &[Vec<String>]) -> Vec<String> {
+        commands
+            .iter()
+            .map(|cmd| format!("{name} ({stage}): {}", cmd.join(" ")))
+            .collect()
+    }
+
+    /// Expand the `${...}` placeholders in every command.
+    pub(crate) fn expand(&self, vars: &Variables) -> Result<Self> {
+        let expand_all = |commands: &[Vec<String>]| -> Result<Vec<Vec<String>>> {
+            commands
+                .iter()
+                .map(|cmd| cmd.iter().map(|arg| vars.expand(arg)).collect())
+                .collect()
+        };
+        Ok(Self {
+            post_install: expand_all(&self.post_install)?,
+            pre_uninstall: expand_all(&self.pre_uninstall)?,
+            post_uninstall: expand_all(&self.post_uninstall)?,
+        })
+    }
+}
+
+/// A license of a tool, which is either a link to the license,
+/// or a path to a file containing the license text.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Hash)]
+pub struct License {
+    pub name: String,
+    pub url: Option<Url>,
+    /// Path to the license file, relative paths are resolved from the manifest.
+    pub path: Option<PathBuf>,
+}
+
+/// A size in bytes, which could also be written as a string with a unit,
+/// such as `"300 MB"` or `"1.5GiB"`.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy, Hash, Default)]
+#[serde(try_from = "RawByteSize", into = "u64")]
+pub struct ByteSize(pub u64);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawByteSize {
+    Bytes(u64),
+    Text(String),
+}
+
+impl TryFrom<RawByteSize> for ByteSize {
+    type Error = anyhow::Error;
+    fn try_from(value: RawByteSize) -> Result<Self> {
+        match value {
+            RawByteSize::Bytes(bytes) => Ok(Self(bytes)),
+            RawByteSize::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl From<ByteSize> for u64 {
+    fn from(value: ByteSize) -> Self {
+        value.0
+    }
+}
+
+impl std::str::FromStr for ByteSize {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: f64 = number
+            .parse()
+            .with_context(|| format!("invalid size '{s}'"))?;
+        let multiplier: u64 = match unit.trim().to_lowercase().as_str() {
+            "" | "b" => 1,
+            "kb" => 1000,
+            "mb" => 1000 * 1000,
+            "gb" => 1000 * 1000 * 1000,
+            "k" | "kib" => 1 << 10,
+            "m" | "mib" => 1 << 20,
+            "g" | "gib" => 1 << 30,
+            _ => {
+                bail!("invalid unit in size '{s}', expecting one of B, KB, MB, GB, KiB, MiB or GiB")
+            }
+        };
+        Ok(Self((number * multiplier as f64) as u64))
+    }
+}
+
+impl std::fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+        let mut size = self.0 as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            write!(f, "{} B", self.0)
+        } else {
+            write!(f, "{size:.1} {}", UNITS[unit])
+        }
+    }
+}
+
+/// Extra options of tools that are installed by `cargo install`.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub struct CargoOptions {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_default_features: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub all_features: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    /// Only install these binaries instead of every binary of the package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bins: Vec<String>,
+    /// Name of the registry (configured in cargo's `config.toml`) to install from.
+    pub registry: Option<String>,
+    /// Url (or mirrors) of a prebuilt archive containing the binaries of this tool,
+    /// which will be tried before falling back to `cargo install`.
+    pub prebuilt: Option<Mirrors>,
+}
+
+impl CargoOptions {
+    /// Convert these options into `cargo install` arguments.
+    pub(crate) fn args(&self) -> Vec<&str> {
+        let mut args = vec![];
+        for feature in &self.features {
+            args.extend(["--features", feature]);
+        }
+        if self.no_default_features {
+            args.push("--no-default-features");
+        }
+        if self.all_features {
+            args.push("--all-features");
+        }
+        if self.locked {
+            args.push("--locked");
+        }
+        for bin in &self.bins {
+            args.extend(["--bin", bin]);
+        }
+        if let Some(registry) = &self.registry {
+            args.extend(["--registry", registry]);
+        }
+        args
+    }
+}
+
+/// The url of a package, or a list of mirrors of it that will be tried in order.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Hash)]
+#[serde(untagged)]
+pub enum Mirrors {
+    Single(Url),
+    List(Vec<Url>),
+}
+
+impl Mirrors {
+    pub fn urls(&self) -> &[Url] {
+        match self {
+            Self::Single(url) => std::slice::from_ref(url),
+            Self::List(urls) => urls,
+        }
+    }
+
+    /// Replace the `${version}` placeholders that were kept in the urls with `version`.
+    ///
+    /// The placeholders are kept when the version of a tool is a requirement (such as `^0.9`),
+    /// since the actual version is only known after resolving it when installing.
+    pub(crate) fn with_version(&self, version: &str) -> Result<Self> {
+        let expand = |url: &Url| -> Result<Url> {
+            // `{` and `}` are percent-encoded in the path of an url
+            let expanded = url
+                .as_str()
+                .replace(VERSION_PLACEHOLDER, version)
+                .replace("$%7Bversion%7D", version);
+            Url::parse(&expanded).with_context(|| format!("invalid url '{expanded}'"))
+        };
+        Ok(match self {
+            Self::Single(url) => Self::Single(expand(url)?),
+            Self::List(urls) => Self::List(urls.iter().map(expand).collect::<Result<_>>()?),
+        })
+    }
+}
+
 impl ToolInfo {
     pub fn is_required(&self) -> bool {
         match self {
@@ -433,6 +1081,112 @@ impl ToolInfo {
         }
     }
 
+    /// Get a list of expected checksums of this tool's package.
+    ///
+    /// Only [`ToolInfo::Path`] and [`ToolInfo::Url`] could have checksums,
+    /// the list will be empty for other variants.
+    pub fn checksums(&self) -> Vec<(HashAlgorithm, &str)> {
+        match self {
+            Self::Path { sha256, sha512, .. } | Self::Url { sha256, sha512, .. } => [
+                (HashAlgorithm::Sha256, sha256.as_deref()),
+                (HashAlgorithm::Sha512, sha512.as_deref()),
+            ]
+            .into_iter()
+            .filter_map(|(algo, sum)| sum.map(|s| (algo, s)))
+            .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Get the names of tools that this tool depends on.
+    pub fn dependencies(&self) -> &[String] {
+        match self {
+            Self::PlainVersion(_) => &[],
+            Self::Git { depends_on, .. }
+            | Self::Path { depends_on, .. }
+            | Self::Url { depends_on, .. }
+            | Self::DetailedVersion { depends_on, .. } => depends_on,
+        }
+    }
+
+    /// Get the names of tools that cannot be installed along with this one.
+    pub fn conflicts(&self) -> &[String] {
+        match self {
+            Self::PlainVersion(_) => &[],
+            Self::Git { conflicts_with, .. }
+            | Self::Path { conflicts_with, .. }
+            | Self::Url { conflicts_with, .. }
+            | Self::DetailedVersion { conflicts_with, .. } => conflicts_with,
+        }
+    }
+
+    fn license_mut(&mut self) -> Option<&mut License> {
+        match self {
+            Self::PlainVersion(_) => None,
+            Self::Git { license, .. }
+            | Self::Path { license, .. }
+            | Self::Url { license, .. }
+            | Self::DetailedVersion { license, .. } => license.as_mut(),
+        }
+    }
+
+    /// Get the license that must be accepted before installing this tool, if there is one.
+    pub fn license(&self) -> Option<&License> {
+        match self {
+            Self::PlainVersion(_) => None,
+            Self::Git { license, .. }
+            | Self::Path { license, .. }
+            | Self::Url { license, .. }
+            | Self::DetailedVersion { license, .. } => license.as_ref(),
+        }
+    }
+
+    /// Get the estimated size of this tool after installation, if there's a `size` hint.
+    pub fn size(&self) -> Option<ByteSize> {
+        match self {
+            Self::PlainVersion(_) => None,
+            Self::Git { size, .. }
+            | Self::Path { size, .. }
+            | Self::Url { size, .. }
+            | Self::DetailedVersion { size, .. } => *size,
+        }
+    }
+
+    /// Get the environment variables to set after installing this tool, the values are not
+    /// expanded yet.
+    pub fn env_vars(&self) -> impl Iterator<Item = (&str, &str)> {
+        let env = match self {
+            Self::PlainVersion(_) => None,
+            Self::Git { env, .. }
+            | Self::Path { env, .. }
+            | Self::Url { env, .. }
+            | Self::DetailedVersion { env, .. } => Some(env),
+        };
+        env.into_iter()
+            .flatten()
+            .map(|(key, val)| (key.as_str(), val.as_str()))
+    }
+
+    /// Get the commands to run at certain stages of this tool's (un)installation.
+    pub fn hooks(&self) -> Option<&Hooks> {
+        match self {
+            Self::PlainVersion(_) => None,
+            Self::Git { hooks, .. }
+            | Self::Path { hooks, .. }
+            | Self::Url { hooks, .. }
+            | Self::DetailedVersion { hooks, .. } => Some(hooks),
+        }
+    }
+
+    /// Get the extra `cargo install` options of this tool,
+    /// only [`ToolInfo::DetailedVersion`] and [`ToolInfo::Git`] could have them.
+    pub fn cargo_options(&self) -> Option<&CargoOptions> {
+        match self {
+            Self::Git { cargo, .. } | Self::DetailedVersion { cargo, .. } => Some(cargo),
+            _ => None,
+        }
+    }
+
     pub fn is_cargo_tool(&self) -> bool {
         matches!(
             self,
@@ -446,49 +1200,112 @@ impl ToolInfo {
                 *self = Self::Path {
                     path,
                     version: Some(ver.to_owned()),
+                    sha256: None,
+                    sha512: None,
                     required: false,
                     optional: false,
+                    depends_on: vec![],
+                    conflicts_with: vec![],
+                    license: None,
+                    size: None,
+                    env: BTreeMap::new(),
+                    hooks: Hooks::default(),
                 };
             }
             Self::Git {
-                required, optional, ..
+                required,
+                optional,
+                depends_on,
+                conflicts_with,
+                license,
+                size,
+                env,
+                hooks,
+                ..
             } => {
                 *self = Self::Path {
                     path,
                     version: None,
+                    sha256: None,
+                    sha512: None,
                     required: *required,
                     optional: *optional,
+                    depends_on: depends_on.to_owned(),
+                    conflicts_with: conflicts_with.to_owned(),
+                    license: license.to_owned(),
+                    size: size.to_owned(),
+                    env: env.to_owned(),
+                    hooks: hooks.to_owned(),
                 };
             }
             Self::Path {
                 version,
+                sha256,
+                sha512,
                 required,
                 optional,
+                depends_on,
+                conflicts_with,
+                license,
+                size,
+                env,
+                hooks,
                 ..
             }
             | Self::Url {
                 version,
+                sha256,
+                sha512,
                 required,
                 optional,
+                depends_on,
+                conflicts_with,
+                license,
+                size,
+                env,
+                hooks,
                 ..
             } => {
                 *self = Self::Path {
                     path,
                     version: version.to_owned(),
+                    sha256: sha256.to_owned(),
+                    sha512: sha512.to_owned(),
                     required: *required,
                     optional: *optional,
+                    depends_on: depends_on.to_owned(),
+                    conflicts_with: conflicts_with.to_owned(),
+                    license: license.to_owned(),
+                    size: size.to_owned(),
+                    env: env.to_owned(),
+                    hooks: hooks.to_owned(),
                 };
             }
             Self::DetailedVersion {
                 ver,
                 required,
                 optional,
+                depends_on,
+                conflicts_with,
+                license,
+                size,
+                env,
+                hooks,
+                ..
             } => {
                 *self = Self::Path {
                     path,
                     version: Some(ver.to_owned()),
+                    sha256: None,
+                    sha512: None,
                     required: *required,
                     optional: *optional,
+                    depends_on: depends_on.to_owned(),
+                    conflicts_with: conflicts_with.to_owned(),
+                    license: license.to_owned(),
+                    size: size.to_owned(),
+                    env: env.to_owned(),
+                    hooks: hooks.to_owned(),
                 }
             }
         }
@@ -509,54 +1326,453 @@ fn baked_in_manifest_raw() -> &'static str {
 /// Get a [`ToolsetManifest`] by either:
 ///
 /// - Download from specific url, which could have file schema.
+///   The downloaded manifest will be verified with its detached signature if
+///   there are trusted keys configured.
 /// - Load from `baked_in_manifest_raw`.
 ///
 pub fn get_toolset_manifest(url: Option<&Url>, insecure: bool) -> Result<ToolsetManifest> {
     if let Some(url) = url {
         let temp = utils::make_temp_file("toolset-manifest-", None)?;
-        // NB: This might fail if the url requires certain proxy setup
-        utils::DownloadOpt::new("toolset manifest")
-            .insecure(insecure)
-            .download_file(url, temp.path(), false)?;
-        ToolsetManifest::load(temp.path())
+        let raw = download_manifest(url, temp.path(), insecure)?;
+        let source = ManifestSource::Url(url.clone());
+        let mut manifest = ToolsetManifest::compose(&raw, &source, insecure)?;
+        manifest.path = Some(temp.path().to_path_buf());
+        Ok(manifest)
     } else {
-        ToolsetManifest::from_str(baked_in_manifest_raw())
+        ToolsetManifest::compose(baked_in_manifest_raw(), &ManifestSource::BakedIn, insecure)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Download a toolset manifest from `url` to `dest`, verify it, then return its content.
+fn download_manifest(url: &Url, dest: &Path, insecure: bool) -> Result<String> {
+    // NB: This might fail if the url requires certain proxy setup
+    utils::DownloadOpt::new("toolset manifest")
+        .insecure(insecure)
+        .download_file(url, dest, false)?;
+    let content = std::fs::read(dest)?;
+    signature::verify_downloaded("toolset manifest", url, &content, insecure)?;
+    Ok(String::from_utf8(content)?)
+}
 
-    /// Convenient macro to initialize **Non-Required** `ToolInfo`
-    macro_rules! tool_info {
-        ($version:literal) => {
-            ToolInfo::PlainVersion($version.into())
+/// Where a toolset manifest was loaded from, used to locate its base manifest (`extends`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ManifestSource {
+    BakedIn,
+    Path(PathBuf),
+    Url(Url),
+}
+
+impl std::fmt::Display for ManifestSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BakedIn => write!(f, "<baked-in manifest>"),
+            Self::Path(p) => write!(f, "{}", p.display()),
+            Self::Url(url) => write!(f, "{url}"),
+        }
+    }
+}
+
+impl ManifestSource {
+    /// The local directory that contains the manifest, which is used to resolve relative paths.
+    ///
+    /// Return `None` if the manifest was loaded from a remote server.
+    fn local_dir(&self) -> Result<Option<PathBuf>> {
+        let dir = match self {
+            Self::BakedIn => Some(ToolsetManifest::default().parent_dir()?),
+            Self::Path(p) => p.parent().map(Path::to_path_buf),
+            Self::Url(url) => url
+                .to_file_path()
+                .ok()
+                .and_then(|p| p.parent().map(Path::to_path_buf)),
         };
-        ($url_str:literal, $version:expr) => {
-            ToolInfo::Url {
-                version: $version.map(ToString::to_string),
-                url: $url_str.parse().unwrap(),
-                required: false,
-                optional: false,
+        Ok(dir)
+    }
+
+    /// Find out where the base manifest is, `extends` could either be a url,
+    /// or a path that is relative to the manifest declaring it.
+    fn resolve(&self, extends: &str) -> Result<Self> {
+        if let Ok(url) = Url::parse(extends) {
+            if matches!(url.scheme(), "http" | "https" | "file") {
+                return Ok(Self::Url(url));
             }
-        };
-        ($git:literal, $branch:expr, $tag:expr, $rev:expr) => {
-            ToolInfo::Git {
-                git: $git.parse().unwrap(),
-                branch: $branch.map(ToString::to_string),
-                tag: $tag.map(ToString::to_string),
+        }
+        if let Self::Url(url) = self {
+            if !url.scheme().eq_ignore_ascii_case("file") {
+                let joined = url
+                    .join(extends)
+                    .with_context(|| format!("invalid `extends` value '{extends}' in '{url}'"))?;
+                return Ok(Self::Url(joined));
+            }
+        }
+        let root = self.local_dir()?;
+        let path = utils::to_nomalized_abspath(extends, root.as_deref())?;
+        Ok(Self::Path(path))
+    }
+
+    /// Read the content of the manifest in this location.
+    fn read(&self, insecure: bool) -> Result<String> {
+        match self {
+            Self::BakedIn => Ok(baked_in_manifest_raw().to_string()),
+            Self::Path(p) => utils::read_to_string("manifest", p),
+            Self::Url(url) => {
+                let temp = utils::make_temp_file("toolset-manifest-", None)?;
+                download_manifest(url, temp.path(), insecure)
+            }
+        }
+    }
+}
+
+/// The `[remove]` section of a manifest that `extends` another,
+/// containing things to remove from the base manifest.
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct Removals {
+    /// Toolchain components to remove from both `components` and `optional-components`.
+    #[serde(default)]
+    components: Vec<String>,
+    /// Tools to remove from every target, including their descriptions and group memberships.
+    #[serde(default)]
+    tools: Vec<String>,
+    /// Groups to remove, the tools in these groups will be kept.
+    #[serde(default)]
+    groups: Vec<String>,
+}
+
+impl Removals {
+    fn apply(&self, base: &mut Table, origins: &mut ToolOrigins) {
+        if let Some(Value::Table(rust)) = base.get_mut("rust") {
+            for key in ["components", "optional-components"] {
+                if let Some(Value::Array(list)) = rust.get_mut(key) {
+                    list.retain(|c| {
+                        !c.as_str()
+                            .is_some_and(|c| self.components.iter().any(|r| r == c))
+                    });
+                }
+            }
+        }
+
+        let Some(Value::Table(tools)) = base.get_mut("tools") else {
+            return;
+        };
+        let is_removed = |name: &str| self.tools.iter().any(|t| t == name);
+        if let Some(Value::Table(descriptions)) = tools.get_mut("descriptions") {
+            descriptions.retain(|name, _| !is_removed(name));
+        }
+        if let Some(Value::Table(groups)) = tools.get_mut("group") {
+            groups.retain(|name, _| !self.groups.iter().any(|g| g == name));
+            for (_, group) in groups.iter_mut() {
+                let members = match group {
+                    Value::Table(detailed) => detailed.get_mut("members"),
+                    members => Some(members),
+                };
+                if let Some(Value::Array(list)) = members {
+                    list.retain(|t| !t.as_str().is_some_and(is_removed));
+                }
+            }
+        }
+        if let Some(Value::Table(targets)) = tools.get_mut("target") {
+            for (_, tool_map) in targets.iter_mut() {
+                if let Value::Table(map) = tool_map {
+                    map.retain(|name, _| !is_removed(name));
+                }
+            }
+        }
+        origins.retain(|(_, name), _| !is_removed(name));
+    }
+}
+
+impl ToolsetManifest {
+    /// Deserialize a manifest from `raw` content, merge it onto its base manifest
+    /// if it `extends` one, then expand the `${...}` placeholders in it.
+    fn compose(raw: &str, source: &ManifestSource, insecure: bool) -> Result<Self> {
+        let table: Table = toml::from_str(raw)?;
+        // check this before deserializing, as the manifest might contain keys that we don't know
+        ensure_manager_version(table.get("min-manager-version").and_then(Value::as_str))?;
+        let extends = table.contains_key("extends");
+        if !extends && !raw.contains("${") {
+            return Self::from_str(raw);
+        }
+
+        let (mut table, origins) = if extends {
+            let mut chain = vec![source.clone()];
+            compose_table(table, source, insecure, &mut chain)?
+        } else {
+            (table, HashMap::new())
+        };
+        expand_variables(&mut table)?;
+        let mut manifest: Self = Value::Table(table).try_into()?;
+        manifest.origins = origins;
+        Ok(manifest)
+    }
+}
+
+/// Expand the `${...}` placeholders (check [`Variables`] for the available ones) in
+/// the url or path of each tool, `offline-dist-server`, and the paths of `rustup` binaries.
+fn expand_variables(table: &mut Table) -> Result<()> {
+    let version = table
+        .get("version")
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned);
+    let rust_version = table
+        .get("rust")
+        .and_then(|rust| rust.get("version"))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let vars = |target: &str| Variables::new(target, &rust_version, version.as_deref());
+
+    if let Some(Value::Table(rust)) = table.get_mut("rust") {
+        if let Some(Value::String(server)) = rust.get_mut("offline-dist-server") {
+            *server = vars(env!("TARGET")).expand(server)?;
+        }
+        if let Some(Value::Table(rustup)) = rust.get_mut("rustup") {
+            for (target, path) in rustup.iter_mut() {
+                if let Value::String(path) = path {
+                    *path = vars(target).expand(path)?;
+                }
+            }
+        }
+    }
+
+    let Some(Value::Table(targets)) = table
+        .get_mut("tools")
+        .and_then(|tools| tools.get_mut("target"))
+    else {
+        return Ok(());
+    };
+    for (target, tools) in targets.iter_mut() {
+        let Value::Table(tools) = tools else {
+            continue;
+        };
+        for tool in tools.iter_mut().filter_map(|(_, tool)| tool.as_table_mut()) {
+            let tool_version = ["version", "ver"]
+                .iter()
+                .find_map(|key| tool.get(*key).and_then(Value::as_str))
+                .map(ToOwned::to_owned);
+            let tool_vars = vars(target).with_version(tool_version.as_deref());
+            let prebuilt_vars = tool_vars
+                .clone()
+                .keep_unresolved_version(tool_version.as_deref());
+            for (key, vars) in [
+                ("url", &tool_vars),
+                ("path", &tool_vars),
+                ("prebuilt", &prebuilt_vars),
+            ] {
+                match tool.get_mut(key) {
+                    Some(Value::String(value)) => *value = vars.expand(value)?,
+                    // `url` and `prebuilt` could be a list of mirrors
+                    Some(Value::Array(values)) => {
+                        for value in values {
+                            if let Value::String(value) = value {
+                                *value = vars.expand(value)?;
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Recursively merge the manifest `table` onto the base manifest it `extends`.
+///
+/// Return the merged table, along with the directories of manifests that declared each tool.
+fn compose_table(
+    mut table: Table,
+    source: &ManifestSource,
+    insecure: bool,
+    chain: &mut Vec<ManifestSource>,
+) -> Result<(Table, ToolOrigins)> {
+    let own_dir = source.local_dir()?;
+    let mut origins = HashMap::new();
+    let mut declared = vec![];
+    if let Some(Value::Table(targets)) = table.get("tools").and_then(|t| t.get("target")) {
+        for (target, tool_map) in targets {
+            if let Value::Table(map) = tool_map {
+                declared.extend(map.keys().map(|name| (target.clone(), name.clone())));
+            }
+        }
+    }
+
+    let Some(extends) = table.remove("extends") else {
+        if let Some(dir) = own_dir {
+            origins.extend(declared.into_iter().map(|key| (key, dir.clone())));
+        }
+        return Ok((table, origins));
+    };
+    let extends = extends
+        .as_str()
+        .ok_or_else(|| anyhow!("`extends` in manifest '{source}' must be a string"))?;
+    let base_source = source.resolve(extends)?;
+    if chain.contains(&base_source) {
+        bail!("cyclic `extends` detected: manifest '{base_source}' was already extended");
+    }
+    chain.push(base_source.clone());
+
+    let base_raw = base_source.read(insecure)?;
+    let base_table: Table = toml::from_str(&base_raw)
+        .with_context(|| format!("unable to parse base manifest '{base_source}'"))?;
+    ensure_manager_version(
+        base_table
+            .get("min-manager-version")
+            .and_then(Value::as_str),
+    )?;
+    let (mut base, mut base_origins) = compose_table(base_table, &base_source, insecure, chain)?;
+
+    if let Some(removals) = table.remove("remove") {
+        let removals: Removals = removals
+            .try_into()
+            .with_context(|| format!("invalid `remove` section in manifest '{source}'"))?;
+        removals.apply(&mut base, &mut base_origins);
+    }
+    // the composed manifest requires whichever manager version is the newest among its layers
+    let min_manager_version = highest_manager_version(&base, &table);
+    merge_manifest_tables(&mut base, table);
+    if let Some(version) = min_manager_version {
+        base.insert("min-manager-version".into(), Value::String(version));
+    }
+
+    for key in declared {
+        match &own_dir {
+            Some(dir) => base_origins.insert(key, dir.clone()),
+            None => base_origins.remove(&key),
+        };
+    }
+    Ok((base, base_origins))
+}
+
+/// Get the highest `min-manager-version` required by either of the manifest tables.
+fn highest_manager_version(base: &Table, overlay: &Table) -> Option<String> {
+    [base, overlay]
+        .into_iter()
+        .filter_map(|table| table.get("min-manager-version").and_then(Value::as_str))
+        .max_by_key(|ver| semver::Version::parse(ver).ok())
+        .map(ToOwned::to_owned)
+}
+
+/// Merge a manifest `overlay` onto its `base`.
+///
+/// `[rust]`, `[tools.descriptions]`, `[tools.group]` and `[tools.target.*]` are deep merged,
+/// where a tool (or a preset in `[presets]`) declared in the overlay replaces the one in base entirely,
+/// other keys are simply overridden.
+fn merge_manifest_tables(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (key.as_str(), base.get_mut(&key), value) {
+            ("rust", Some(Value::Table(base_rust)), Value::Table(rust)) => {
+                deep_merge(base_rust, rust);
+            }
+            ("presets", Some(Value::Table(base_presets)), Value::Table(presets)) => {
+                base_presets.extend(presets);
+            }
+            ("tools", Some(Value::Table(base_tools)), Value::Table(tools)) => {
+                for (section, value) in tools {
+                    match (section.as_str(), base_tools.get_mut(&section), value) {
+                        ("target", Some(Value::Table(base_targets)), Value::Table(targets)) => {
+                            for (target, tool_map) in targets {
+                                match (base_targets.get_mut(&target), tool_map) {
+                                    (Some(Value::Table(base_map)), Value::Table(map)) => {
+                                        base_map.extend(map);
+                                    }
+                                    (_, tool_map) => {
+                                        base_targets.insert(target, tool_map);
+                                    }
+                                }
+                            }
+                        }
+                        (_, Some(Value::Table(base_section)), Value::Table(section_table)) => {
+                            deep_merge(base_section, section_table);
+                        }
+                        (_, _, value) => {
+                            base_tools.insert(section, value);
+                        }
+                    }
+                }
+            }
+            (_, _, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Merge tables recursively, arrays are combined without duplicates,
+/// and other values in `overlay` will override the ones in `base`.
+fn deep_merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_table)), Value::Table(table)) => deep_merge(base_table, table),
+            (Some(Value::Array(base_list)), Value::Array(list)) => {
+                for item in list {
+                    if !base_list.contains(&item) {
+                        base_list.push(item);
+                    }
+                }
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Convenient macro to initialize **Non-Required** `ToolInfo`
+    macro_rules! tool_info {
+        ($version:literal) => {
+            ToolInfo::PlainVersion($version.into())
+        };
+        ($url_str:literal, $version:expr) => {
+            ToolInfo::Url {
+                version: $version.map(ToString::to_string),
+                url: Mirrors::Single($url_str.parse().unwrap()),
+                sha256: None,
+                sha512: None,
+                required: false,
+                optional: false,
+                depends_on: vec![],
+                conflicts_with: vec![],
+                license: None,
+                size: None,
+                env: BTreeMap::new(),
+                hooks: Hooks::default(),
+            }
+        };
+        ($git:literal, $branch:expr, $tag:expr, $rev:expr) => {
+            ToolInfo::Git {
+                git: $git.parse().unwrap(),
+                branch: $branch.map(ToString::to_string),
+                tag: $tag.map(ToString::to_string),
                 rev: $rev.map(ToString::to_string),
                 required: false,
                 optional: false,
+                depends_on: vec![],
+                conflicts_with: vec![],
+                license: None,
+                size: None,
+                env: BTreeMap::new(),
+                hooks: Hooks::default(),
+                cargo: CargoOptions::default(),
             }
         };
         ($path:expr, $version:expr) => {
             ToolInfo::Path {
                 version: $version.map(ToString::to_string),
                 path: $path,
+                sha256: None,
+                sha512: None,
                 required: false,
                 optional: false,
+                depends_on: vec![],
+                conflicts_with: vec![],
+                license: None,
+                size: None,
+                env: BTreeMap::new(),
+                hooks: Hooks::default(),
             }
         };
     }
@@ -716,7 +1932,7 @@ t4 = { git = "https://git.example.com/org/tool", branch = "stable" }
         #[cfg(all(windows, target_env = "gnu"))]
         assert_eq!(
             tools.unwrap(),
-            &ToolMap::from([
+            ToolMap::from([
                 (
                     "mingw64".into(),
                     tool_info!(
@@ -749,7 +1965,7 @@ t4 = { git = "https://git.example.com/org/tool", branch = "stable" }
         #[cfg(all(windows, target_env = "msvc"))]
         assert_eq!(
             tools.unwrap(),
-            &ToolMap::from([
+            ToolMap::from([
                 (
                     "buildtools".into(),
                     tool_info!(
@@ -786,7 +2002,7 @@ t4 = { git = "https://git.example.com/org/tool", branch = "stable" }
         );
 
         #[cfg(all(target_arch = "x86_64", target_os = "linux", target_env = "gnu"))]
-        assert_eq!(tools.unwrap(), &ToolMap::from([
+        assert_eq!(tools.unwrap(), ToolMap::from([
             ("cargo-llvm-cov".into(), tool_info!("https://github.com/taiki-e/cargo-llvm-cov/releases/download/v0.6.11/cargo-llvm-cov-x86_64-unknown-linux-gnu.tar.gz", Some("0.6.11"))),
             ("flamegraph".into(), tool_info!("https://github.com/flamegraph-rs/flamegraph", None::<&str>, Some("v0.6.5"), None::<&str>)),
             ("cargo-expand".into(), tool_info!("1.0.88")),
@@ -795,6 +2011,58 @@ t4 = { git = "https://git.example.com/org/tool", branch = "stable" }
         // TODO: Add test for macos.
     }
 
+    #[test]
+    fn cfg_target_keys() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-unknown-linux-gnu]
+t1 = "0.1.0"
+
+[tools.target.'cfg(unix)']
+t1 = "0.2.0"
+t2 = "0.2.0"
+
+[tools.target.'cfg(all(target_os = "linux", target_arch = "x86_64"))']
+t3 = "0.3.0"
+
+[tools.target.'cfg(windows)']
+t4 = "0.4.0"
+"#;
+        let manifest = ToolsetManifest::from_str(input).unwrap();
+
+        let linux_x64 = manifest
+            .tools_for_target("x86_64-unknown-linux-gnu")
+            .unwrap();
+        assert_eq!(
+            linux_x64,
+            ToolMap::from([
+                ("t1".into(), tool_info!("0.1.0")),
+                ("t3".into(), tool_info!("0.3.0")),
+                ("t2".into(), tool_info!("0.2.0")),
+            ])
+        );
+
+        let linux_arm = manifest
+            .tools_for_target("aarch64-unknown-linux-musl")
+            .unwrap();
+        assert_eq!(
+            linux_arm,
+            ToolMap::from([
+                ("t1".into(), tool_info!("0.2.0")),
+                ("t2".into(), tool_info!("0.2.0")),
+            ])
+        );
+
+        let windows = manifest.tools_for_target("x86_64-pc-windows-msvc").unwrap();
+        assert_eq!(windows, ToolMap::from([("t4".into(), tool_info!("0.4.0"))]));
+
+        assert!(manifest
+            .tools_for_target("wasm32-unknown-unknown")
+            .is_none());
+    }
+
     #[test]
     fn with_tools_descriptions() {
         let input = r#"
@@ -818,16 +2086,46 @@ t3 = { url = "https://example.com/path/to/tool" }
         assert_eq!(
             expected.tools.descriptions,
             BTreeMap::from_iter([
-                ("t1".to_string(), "desc for t1".to_string()),
-                ("t3".to_string(), "desc for t3".to_string()),
-                (
-                    "t4".to_string(),
-                    "desc for t4 that might not exist".to_string()
-                ),
+                ("t1".to_string(), "desc for t1".into()),
+                ("t3".to_string(), "desc for t3".into()),
+                ("t4".to_string(), "desc for t4 that might not exist".into()),
             ])
         );
     }
 
+    #[test]
+    fn with_localized_descriptions() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+name = { en = "Rust Toolchain", zh-CN = "Rust 工具链" }
+profile = { name = "minimal", description = { en = "The minimal toolchain", zh-CN = "最小工具链" } }
+
+[tools.descriptions]
+t1 = "desc for t1"
+t2 = { en = "desc for t2", zh-CN = "t2 的描述" }
+t3 = { zh-CN = "t3 的描述" }
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        let desc = |name: &str| &expected.tools.descriptions[name];
+        assert_eq!(desc("t1").get_for("zh-CN"), "desc for t1");
+        assert_eq!(desc("t2").get_for("zh-CN"), "t2 的描述");
+        assert_eq!(desc("t2").get_for("zh_cn"), "t2 的描述");
+        assert_eq!(desc("t2").get_for("zh-TW"), "t2 的描述");
+        assert_eq!(desc("t2").get_for("en-US"), "desc for t2");
+        assert_eq!(desc("t2").get_for("fr"), "desc for t2");
+        assert_eq!(desc("t3").get_for("en"), "t3 的描述");
+
+        let rust_name = expected.rust.name.as_ref().unwrap();
+        assert_eq!(rust_name.get_for("zh-CN"), "Rust 工具链");
+        let profile = expected.toolchain_profile().unwrap();
+        assert_eq!(
+            profile.description.as_ref().unwrap().get_for("en"),
+            "The minimal toolchain"
+        );
+    }
+
     #[test]
     fn with_required_property() {
         let input = r#"
@@ -887,11 +2185,11 @@ Others = [ "t3", "t4" ]
             BTreeMap::from_iter([
                 (
                     "Some Group".to_string(),
-                    ["t1".to_string(), "t2".to_string()].into_iter().collect()
+                    ToolGroup::Members(["t1".to_string(), "t2".to_string()].into_iter().collect())
                 ),
                 (
                     "Others".to_string(),
-                    ["t3".to_string(), "t4".to_string()].into_iter().collect()
+                    ToolGroup::Members(["t3".to_string(), "t4".to_string()].into_iter().collect())
                 )
             ])
         );
@@ -900,6 +2198,135 @@ Others = [ "t3", "t4" ]
         assert_eq!(expected.group_name("t100"), None);
     }
 
+    #[test]
+    fn with_conflicts() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.group]
+Prerequisites = [ "t1" ]
+Linkers = { members = [ "mold", "lld", "sold" ], choose-one = true }
+
+[tools.target.x86_64-unknown-linux-gnu]
+t1 = { ver = "0.1.0", conflicts-with = [ "t2" ] }
+t2 = "0.1.0"
+mold = "2.0.0"
+lld = "0.1.0"
+sold = "0.1.0"
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        let tools = expected
+            .tools_for_target("x86_64-unknown-linux-gnu")
+            .unwrap();
+        assert_eq!(expected.group_name("lld"), Some("Linkers"));
+        assert_eq!(expected.conflicts_of("t1", &tools), ["t2"]);
+        // conflicts are mutual
+        assert_eq!(expected.conflicts_of("t2", &tools), ["t1"]);
+        assert_eq!(expected.conflicts_of("mold", &tools), ["lld", "sold"]);
+    }
+
+    #[test]
+    fn with_min_manager_version() {
+        let input = r#"
+min-manager-version = "0.0.1"
+[rust]
+version = "1.0.0"
+"#;
+        let manifest = ToolsetManifest::compose(input, &ManifestSource::BakedIn, false).unwrap();
+        assert_eq!(manifest.min_manager_version.as_deref(), Some("0.0.1"));
+
+        let input = r#"
+min-manager-version = "999.0.0"
+[rust]
+version = "1.0.0"
+new-key-from-the-future = { a = 1 }
+"#;
+        let err = ToolsetManifest::compose(input, &ManifestSource::BakedIn, false).unwrap_err();
+        let too_old = crate::update::ManagerTooOld::find_in(&err).unwrap();
+        assert_eq!(too_old.required, semver::Version::new(999, 0, 0));
+    }
+
+    #[test]
+    fn with_presets() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[presets]
+minimal = { description = "The bare minimum", components = [] }
+full = { components = [ "t1", "t2" ] }
+
+[tools.target.x86_64-unknown-linux-gnu]
+t1 = "0.1.0"
+t2 = "0.1.0"
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        assert_eq!(
+            expected.presets().keys().collect::<Vec<_>>(),
+            ["full", "minimal"]
+        );
+        let minimal = expected.preset("minimal").unwrap();
+        assert_eq!(minimal.description.as_deref(), Some("The bare minimum"));
+        assert!(minimal.components.is_empty());
+        let full = expected.preset("full").unwrap();
+        assert!(full.contains("t1") && full.contains("t2"));
+        assert!(expected.preset("embedded").is_err());
+    }
+
+    #[test]
+    fn with_license() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+t1 = { ver = "0.1.0", license = { name = "MIT", url = "https://opensource.org/license/mit" } }
+t2 = { path = "packages/t2.zip", license = { name = "EULA", path = "licenses/t2.txt" } }
+t3 = "0.1.0"
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        let tools = expected.tools_for_target("x86_64-pc-windows-msvc").unwrap();
+        let t1 = tools["t1"].license().unwrap();
+        assert_eq!(t1.name, "MIT");
+        assert_eq!(
+            t1.url.as_ref().map(|u| u.as_str()),
+            Some("https://opensource.org/license/mit")
+        );
+        let t2 = tools["t2"].license().unwrap();
+        assert_eq!(t2.name, "EULA");
+        assert_eq!(t2.path.as_deref(), Some(Path::new("licenses/t2.txt")));
+        assert!(tools["t3"].license().is_none());
+    }
+
+    #[test]
+    fn with_size_hints() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+t1 = { ver = "0.1.0", size = 1048576 }
+t2 = { path = "packages/t2.zip", size = "300 MB" }
+t3 = { url = "https://example.com/t3.zip", version = "0.1.0", size = "1.5GiB" }
+t4 = "0.1.0"
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        let tools = expected.tools_for_target("x86_64-pc-windows-msvc").unwrap();
+        assert_eq!(tools["t1"].size(), Some(ByteSize(1 << 20)));
+        assert_eq!(tools["t2"].size(), Some(ByteSize(300_000_000)));
+        assert_eq!(tools["t3"].size(), Some(ByteSize(3 << 29)));
+        assert!(tools["t4"].size().is_none());
+
+        assert_eq!(ByteSize(1 << 20).to_string(), "1.0 MiB");
+        assert_eq!(ByteSize(512).to_string(), "512 B");
+        assert!("12 parsecs".parse::<ByteSize>().is_err());
+    }
+
     #[test]
     fn with_optional_toolchain_components() {
         let input = r#"
@@ -915,6 +2342,28 @@ optional-components = ["opt_c1", "opt_c2"]
         assert_eq!(expected.rust.optional_components, vec!["opt_c1", "opt_c2"]);
     }
 
+    #[test]
+    fn with_toolchain_targets() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+targets = ["wasm32-unknown-unknown"]
+optional-targets = ["aarch64-unknown-linux-gnu"]
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        assert_eq!(expected.rust.targets, vec!["wasm32-unknown-unknown"]);
+        assert_eq!(
+            expected.optional_toolchain_targets(),
+            ["aarch64-unknown-linux-gnu"]
+        );
+
+        let components = expected.current_target_components(false).unwrap();
+        let target = components.last().unwrap();
+        assert_eq!(target.name, "aarch64-unknown-linux-gnu");
+        assert!(target.is_toolchain_component && target.is_target && target.optional);
+    }
+
     #[test]
     fn all_toolchain_components_with_flag() {
         let input = r#"
@@ -952,7 +2401,14 @@ t3 = { ver = "0.3.0", optional = true } # use cargo install
             Some(&ToolInfo::DetailedVersion {
                 ver: "0.2.0".into(),
                 required: true,
-                optional: false
+                optional: false,
+                depends_on: vec![],
+                conflicts_with: vec![],
+                license: None,
+                size: None,
+                env: BTreeMap::new(),
+                hooks: Hooks::default(),
+                cargo: CargoOptions::default(),
             })
         );
         assert_eq!(
@@ -960,11 +2416,239 @@ t3 = { ver = "0.3.0", optional = true } # use cargo install
             Some(&ToolInfo::DetailedVersion {
                 ver: "0.3.0".into(),
                 required: false,
-                optional: true
+                optional: true,
+                depends_on: vec![],
+                conflicts_with: vec![],
+                license: None,
+                size: None,
+                env: BTreeMap::new(),
+                hooks: Hooks::default(),
+                cargo: CargoOptions::default(),
             })
         );
     }
 
+    #[test]
+    fn with_dependencies() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+t1 = "0.1.0"
+t2 = { ver = "0.2.0", depends-on = ["t1"] }
+t3 = { path = "/path/to/local", depends-on = ["t1", "t2"] }
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        let tools = expected.tools.target.get("x86_64-pc-windows-msvc").unwrap();
+        assert!(tools["t1"].dependencies().is_empty());
+        assert_eq!(tools["t2"].dependencies(), ["t1"]);
+        assert_eq!(tools["t3"].dependencies(), ["t1", "t2"]);
+    }
+
+    #[test]
+    fn with_checksums() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+t1 = { path = "/path/to/local", sha256 = "abc" }
+t2 = { url = "https://example.com/path/to/tool", version = "0.1.0", sha256 = "abc", sha512 = "def" }
+t3 = { url = "https://example.com/path/to/tool" }
+t4 = "0.1.0"
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        let tools = expected.tools.target.get("x86_64-pc-windows-msvc").unwrap();
+        assert_eq!(
+            tools.get("t1").unwrap().checksums(),
+            vec![(HashAlgorithm::Sha256, "abc")]
+        );
+        assert_eq!(
+            tools.get("t2").unwrap().checksums(),
+            vec![
+                (HashAlgorithm::Sha256, "abc"),
+                (HashAlgorithm::Sha512, "def")
+            ]
+        );
+        assert!(tools.get("t3").unwrap().checksums().is_empty());
+        assert!(tools.get("t4").unwrap().checksums().is_empty());
+    }
+
+    #[test]
+    fn with_env_vars() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+t1 = { path = "/path/to/llvm", env = { LIBCLANG_PATH = "${tool_dir}/bin", LLVM_HOME = "${tool_dir}" } }
+t2 = "0.1.0"
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        let tools = expected.tools.target.get("x86_64-pc-windows-msvc").unwrap();
+        assert_eq!(
+            tools.get("t1").unwrap().env_vars().collect::<Vec<_>>(),
+            [
+                ("LIBCLANG_PATH", "${tool_dir}/bin"),
+                ("LLVM_HOME", "${tool_dir}")
+            ]
+        );
+        assert_eq!(tools.get("t2").unwrap().env_vars().count(), 0);
+    }
+
+    #[test]
+    fn with_cargo_options() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+t1 = { ver = "0.1.0", features = ["a", "b"], no-default-features = true, locked = true }
+t2 = { git = "https://git.example.com/org/tool", tag = "v0.1.0", bins = ["tool"], registry = "my-registry" }
+t3 = "0.1.0"
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        let tools = expected.tools.target.get("x86_64-pc-windows-msvc").unwrap();
+        let args = |name: &str| tools.get(name).unwrap().cargo_options().map(|o| o.args());
+        assert_eq!(
+            args("t1").unwrap(),
+            [
+                "--features",
+                "a",
+                "--features",
+                "b",
+                "--no-default-features",
+                "--locked"
+            ]
+        );
+        assert_eq!(
+            args("t2").unwrap(),
+            ["--bin", "tool", "--registry", "my-registry"]
+        );
+        assert!(args("t3").is_none());
+    }
+
+    #[test]
+    fn with_prebuilt_archive() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-unknown-linux-gnu]
+cargo-nextest = { ver = "0.9.85", locked = true, prebuilt = "https://example.com/cargo-nextest-${version}-${target}.tar.gz" }
+"#;
+
+        let expected = ToolsetManifest::compose(input, &ManifestSource::BakedIn, false).unwrap();
+        let tools = expected
+            .tools
+            .target
+            .get("x86_64-unknown-linux-gnu")
+            .unwrap();
+        let options = tools.get("cargo-nextest").unwrap().cargo_options().unwrap();
+        assert_eq!(
+            options.prebuilt.as_ref().unwrap().urls()[0].as_str(),
+            "https://example.com/cargo-nextest-0.9.85-x86_64-unknown-linux-gnu.tar.gz"
+        );
+        // `prebuilt` is not a `cargo install` argument
+        assert_eq!(options.args(), ["--locked"]);
+    }
+
+    #[test]
+    fn prebuilt_archive_with_version_requirement() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-unknown-linux-gnu]
+cargo-nextest = { ver = "^0.9", prebuilt = ["https://example.com/cargo-nextest-${version}-${target}.tar.gz", "https://example.com/nextest?ver=${version}"] }
+"#;
+
+        let expected = ToolsetManifest::compose(input, &ManifestSource::BakedIn, false).unwrap();
+        let tools = expected
+            .tools
+            .target
+            .get("x86_64-unknown-linux-gnu")
+            .unwrap();
+        let options = tools.get("cargo-nextest").unwrap().cargo_options().unwrap();
+        // the requirement is only resolved when installing
+        let prebuilt = options
+            .prebuilt
+            .as_ref()
+            .unwrap()
+            .with_version("0.9.85")
+            .unwrap();
+        let urls: Vec<_> = prebuilt.urls().iter().map(Url::as_str).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://example.com/cargo-nextest-0.9.85-x86_64-unknown-linux-gnu.tar.gz",
+                "https://example.com/nextest?ver=0.9.85"
+            ]
+        );
+    }
+
+    #[test]
+    fn with_hooks() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+t1 = { path = "/path/to/tool", post-install = [["${tool_dir}/bin/tool", "--init"]], pre-uninstall = [["tool", "--clean"], ["tool", "--reset"]] }
+t2 = "0.1.0"
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        let tools = expected.tools.target.get("x86_64-pc-windows-msvc").unwrap();
+        let hooks = tools.get("t1").unwrap().hooks().unwrap();
+        assert_eq!(
+            Hooks::describe("t1", "post-install", &hooks.post_install),
+            ["t1 (post-install): ${tool_dir}/bin/tool --init"]
+        );
+        assert_eq!(hooks.pre_uninstall.len(), 2);
+        assert!(hooks.post_uninstall.is_empty());
+        assert!(tools.get("t2").unwrap().hooks().is_none());
+
+        let vars =
+            Variables::new("x86_64-pc-windows-msvc", "1.0.0", None).with("tool_dir", "/tools/t1");
+        let expanded = hooks.expand(&vars).unwrap();
+        assert_eq!(expanded.post_install, [["/tools/t1/bin/tool", "--init"]]);
+    }
+
+    #[test]
+    fn with_url_mirrors() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+t1 = { url = "https://example.com/path/to/tool", version = "0.1.0" }
+t2 = { url = ["https://github.com/path/to/tool", "https://mirror.example.com/path/to/tool"], version = "0.1.0" }
+"#;
+
+        let expected = ToolsetManifest::from_str(input).unwrap();
+        let tools = expected.tools.target.get("x86_64-pc-windows-msvc").unwrap();
+        let urls = |name: &str| {
+            let Some(ToolInfo::Url { url, .. }) = tools.get(name) else {
+                panic!("tool '{name}' should be installed from url");
+            };
+            url.urls().iter().map(Url::as_str).collect::<Vec<_>>()
+        };
+        assert_eq!(urls("t1"), ["https://example.com/path/to/tool"]);
+        assert_eq!(
+            urls("t2"),
+            [
+                "https://github.com/path/to/tool",
+                "https://mirror.example.com/path/to/tool"
+            ]
+        );
+    }
+
     #[test]
     fn with_rust_toolchain_name() {
         let specified = r#"
@@ -1091,6 +2775,65 @@ x86_64-unknown-linux-gnu = "packages/x86_64-unknown-linux-gnu/rustup-init"
         assert_eq!(expected.rustup_bin().unwrap().unwrap(), path);
     }
 
+    #[test]
+    fn with_variables() {
+        let input = r#"
+version = "2.0"
+
+[rust]
+version = "1.80.0"
+offline-dist-server = "packages/${version}"
+
+[rust.rustup]
+aarch64-apple-darwin = "packages/${target}/rustup-init"
+
+[tools.target.x86_64-unknown-linux-gnu]
+a = { url = "https://example.com/a-${version}-${os}-${arch}.tar.gz", version = "0.1.0" }
+b = { path = "packages/${rust.version}/${target}/b" }
+"#;
+        let manifest = ToolsetManifest::compose(input, &ManifestSource::BakedIn, false).unwrap();
+        assert_eq!(
+            manifest.rust.offline_dist_server.as_deref(),
+            Some("packages/2.0")
+        );
+        assert_eq!(
+            manifest.rust.rustup["aarch64-apple-darwin"],
+            "packages/aarch64-apple-darwin/rustup-init"
+        );
+
+        let tools = &manifest.tools.target["x86_64-unknown-linux-gnu"];
+        let ToolInfo::Url { url, .. } = &tools["a"] else {
+            panic!("tool `a` should be installed from url");
+        };
+        assert_eq!(
+            url.urls()[0].as_str(),
+            "https://example.com/a-0.1.0-linux-x86_64.tar.gz"
+        );
+        let ToolInfo::Path { path, .. } = &tools["b"] else {
+            panic!("tool `b` should be installed from path");
+        };
+        assert_eq!(
+            path,
+            Path::new("packages/1.80.0/x86_64-unknown-linux-gnu/b")
+        );
+    }
+
+    #[test]
+    fn unknown_variable() {
+        let input = r#"
+[rust]
+version = "1.80.0"
+
+[tools.target.x86_64-unknown-linux-gnu]
+a = { path = "packages/${version}/a" }
+"#;
+        let err = ToolsetManifest::compose(input, &ManifestSource::BakedIn, false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown variable '${version}' in 'packages/${version}/a'"
+        );
+    }
+
     #[test]
     fn with_product_info() {
         let input = r#"
@@ -1104,4 +2847,127 @@ version = "1.0.0"
         assert_eq!(expected.name.unwrap(), "my toolkit");
         assert_eq!(expected.version.unwrap(), "1.0");
     }
+
+    #[test]
+    fn extends_base_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        let base = r#"
+name = "base"
+
+[rust]
+version = "1.0.0"
+components = ["clippy", "rustfmt", "rust-docs"]
+optional-components = ["llvm-tools"]
+
+[tools.descriptions]
+t1 = "tool 1"
+t2 = "tool 2"
+
+[tools.group]
+Prerequisites = ["t1", "t2"]
+
+[presets]
+full = { components = ["t1", "t2"] }
+
+[tools.target.x86_64-unknown-linux-gnu]
+t1 = { path = "packages/t1" }
+t2 = "0.1.0"
+"#;
+        let overlay = r#"
+extends = "../base.toml"
+name = "overlay"
+
+[rust]
+components = ["rust-src"]
+
+[remove]
+components = ["rust-docs"]
+tools = ["t2"]
+
+[tools.descriptions]
+t3 = "tool 3"
+
+[tools.group]
+Prerequisites = ["t3"]
+
+[presets]
+minimal = { components = ["t1"] }
+
+[tools.target.x86_64-unknown-linux-gnu]
+t3 = { path = "packages/t3" }
+"#;
+        std::fs::create_dir(root.path().join("team")).unwrap();
+        std::fs::write(root.path().join("base.toml"), base).unwrap();
+        let overlay_path = root.path().join("team").join("overlay.toml");
+        std::fs::write(&overlay_path, overlay).unwrap();
+
+        let mut manifest = ToolsetManifest::load(&overlay_path).unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("overlay"));
+        assert_eq!(manifest.rust_version(), "1.0.0");
+        assert_eq!(manifest.rust.components, ["clippy", "rustfmt", "rust-src"]);
+        assert_eq!(manifest.optional_toolchain_components(), ["llvm-tools"]);
+        assert_eq!(manifest.get_tool_description("t1"), Some("tool 1"));
+        assert_eq!(manifest.get_tool_description("t2"), None);
+        assert_eq!(manifest.get_tool_description("t3"), Some("tool 3"));
+        assert_eq!(manifest.group_name("t3"), Some("Prerequisites"));
+        assert_eq!(
+            manifest.presets().keys().collect::<Vec<_>>(),
+            ["full", "minimal"]
+        );
+
+        manifest.adjust_paths().unwrap();
+        let tools = &manifest.tools.target["x86_64-unknown-linux-gnu"];
+        assert_eq!(tools.keys().collect::<Vec<_>>(), ["t1", "t3"]);
+        assert_eq!(
+            tools["t1"],
+            tool_info!(root.path().join("packages").join("t1"), None::<&str>)
+        );
+        assert_eq!(
+            tools["t3"],
+            tool_info!(
+                root.path().join("team").join("packages").join("t3"),
+                None::<&str>
+            )
+        );
+    }
+
+    #[test]
+    fn min_manager_version_of_base_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path().join("base.toml");
+        let overlay = root.path().join("overlay.toml");
+        std::fs::write(
+            &overlay,
+            "extends = \"base.toml\"\nmin-manager-version = \"0.0.1\"",
+        )
+        .unwrap();
+
+        std::fs::write(
+            &base,
+            "min-manager-version = \"0.0.2\"\n[rust]\nversion = \"1.0.0\"",
+        )
+        .unwrap();
+        let manifest = ToolsetManifest::load(&overlay).unwrap();
+        assert_eq!(manifest.min_manager_version.as_deref(), Some("0.0.2"));
+
+        std::fs::write(
+            &base,
+            "min-manager-version = \"999.0.0\"\n[rust]\nversion = \"1.0.0\"",
+        )
+        .unwrap();
+        let err = ToolsetManifest::load(&overlay).unwrap_err();
+        let too_old = crate::update::ManagerTooOld::find_in(&err).unwrap();
+        assert_eq!(too_old.required, semver::Version::new(999, 0, 0));
+    }
+
+    #[test]
+    fn cyclic_extends() {
+        let root = tempfile::tempdir().unwrap();
+        let a = root.path().join("a.toml");
+        std::fs::write(&a, "extends = \"b.toml\"\n[rust]\nversion = \"1.0.0\"").unwrap();
+        std::fs::write(root.path().join("b.toml"), "extends = \"a.toml\"").unwrap();
+
+        let err = ToolsetManifest::load(&a).unwrap_err();
+        assert!(err.to_string().contains("cyclic `extends`"));
+    }
 }
@@ -0,0 +1,225 @@
+//! Checks that run before installation, which catch the problems that would otherwise
+//! make an installation fail halfway, such as a full disk or a read-only directory.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use log::{debug, warn};
+
+use super::parser::TomlParser;
+use crate::fingerprint::InstallationRecord;
+use crate::toolset_manifest::{ByteSize, Proxy, ToolInfo, ToolMap};
+use crate::utils::{self, DownloadOpt};
+
+/// Rough size of a Rust toolchain after installation.
+const TOOLCHAIN_SIZE: u64 = 1 << 30;
+/// Free space needed in the system's temp directory, where manifests are downloaded to.
+const MIN_TEMP_SPACE: u64 = 16 << 20;
+
+/// Characters in the installation path that cannot be written into environment variables
+/// (such as `CARGO_HOME`) or the `PATH` variable safely.
+#[cfg(windows)]
+const UNSUPPORTED_CHARS: &[char] = &[';', '"', '\'', '$', '`', '%'];
+#[cfg(not(windows))]
+const UNSUPPORTED_CHARS: &[char] = &[':', '"', '\'', '$', '`', '\\'];
+
+/// A problem found by the preflight checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreflightIssue {
+    RootDir,
+    InvalidUnicode,
+    UnsupportedChar(char),
+    /// The installation path is inside of an existing installation (the root of it).
+    NestedInstallation(PathBuf),
+    NotWritable(PathBuf),
+    InsufficientSpace {
+        path: PathBuf,
+        required: ByteSize,
+        available: ByteSize,
+    },
+}
+
+impl PreflightIssue {
+    /// Whether this issue prevents the installation, which is not the case for insufficient
+    /// space, as the required space is only an estimation.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, Self::InsufficientSpace { .. })
+    }
+}
+
+impl fmt::Display for PreflightIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::RootDir => t!("notify_root_dir"),
+            Self::InvalidUnicode => t!("preflight_invalid_unicode"),
+            Self::UnsupportedChar(c) => t!("preflight_unsupported_char", char = c),
+            Self::NestedInstallation(root) => {
+                t!("preflight_nested_installation", root = root.display())
+            }
+            Self::NotWritable(dir) => t!("preflight_not_writable", dir = dir.display()),
+            Self::InsufficientSpace {
+                path,
+                required,
+                available,
+            } => t!(
+                "preflight_insufficient_space",
+                path = path.display(),
+                required = required,
+                available = available
+            ),
+        };
+        write!(f, "{msg}")
+    }
+}
+
+/// Check if `install_dir` could be used for installation, without checking the free space.
+pub fn check_path(install_dir: &Path) -> Vec<PreflightIssue> {
+    let mut issues = vec![];
+    if utils::is_root_dir(install_dir) {
+        issues.push(PreflightIssue::RootDir);
+    }
+    match install_dir.to_str() {
+        Some(path) => {
+            if let Some(c) = path
+                .chars()
+                .find(|c| UNSUPPORTED_CHARS.contains(c) || c.is_control())
+            {
+                issues.push(PreflightIssue::UnsupportedChar(c));
+            }
+        }
+        None => issues.push(PreflightIssue::InvalidUnicode),
+    }
+    if let Some(root) = install_dir
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join(InstallationRecord::FILENAME).is_file())
+    {
+        issues.push(PreflightIssue::NestedInstallation(root.to_path_buf()));
+    }
+    if !utils::is_writable(install_dir) {
+        let dir = utils::existing_ancestor(install_dir).unwrap_or(install_dir);
+        issues.push(PreflightIssue::NotWritable(dir.to_path_buf()));
+    }
+    issues
+}
+
+/// Check if there's enough free space in the volume of `install_dir` to install something
+/// of `required` bytes, and in the volume of the system's temp directory.
+pub fn check_space(install_dir: &Path, required: u64) -> Vec<PreflightIssue> {
+    let temp_dir = std::env::temp_dir();
+    [
+        (install_dir, required),
+        (temp_dir.as_path(), MIN_TEMP_SPACE),
+    ]
+    .into_iter()
+    .filter_map(|(path, required)| match utils::available_space(path) {
+        Ok(available) if available < required => Some(PreflightIssue::InsufficientSpace {
+            path: path.to_path_buf(),
+            required: ByteSize(required),
+            available: ByteSize(available),
+        }),
+        Ok(_) => None,
+        Err(e) => {
+            debug!("skipping free space check: {e:?}");
+            None
+        }
+    })
+    .collect()
+}
+
+/// Options to ask the server for the size of packages that do not have a `size` hint.
+pub struct RemoteSize<'a> {
+    pub proxy: Option<&'a Proxy>,
+    pub insecure: bool,
+}
+
+/// Estimate the space needed to install `tools`, and a Rust toolchain if `with_toolchain`.
+///
+/// The `size` hints of tools are used if there are any, otherwise the size of their packages,
+/// which are only known for local packages, or downloaded ones if `remote` is given.
+pub fn estimate_size(tools: &ToolMap, with_toolchain: bool, remote: Option<RemoteSize>) -> u64 {
+    let toolchain = if with_toolchain { TOOLCHAIN_SIZE } else { 0 };
+    let tools_size = tools
+        .iter()
+        .filter_map(|(name, tool)| {
+            tool.size()
+                .map(u64::from)
+                .or_else(|| package_size(name, tool, remote.as_ref()))
+        })
+        .sum::<u64>();
+    toolchain + tools_size
+}
+
+fn package_size(name: &str, tool: &ToolInfo, remote: Option<&RemoteSize>) -> Option<u64> {
+    match tool {
+        ToolInfo::Path { path, .. } => path
+            .metadata()
+            .ok()
+            .filter(|m| m.is_file())
+            .map(|m| m.len()),
+        ToolInfo::Url { url, .. } => {
+            let remote = remote?;
+            url.urls().iter().find_map(|url| {
+                DownloadOpt::new(name)
+                    .proxy(remote.proxy.cloned())
+                    .insecure(remote.insecure)
+                    .content_length(url)
+                    .ok()
+                    .flatten()
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Report the `issues` found by preflight checks, fails if any of them is fatal.
+pub(crate) fn ensure_passed(issues: &[PreflightIssue]) -> Result<()> {
+    let (fatal, others): (Vec<_>, Vec<_>) = issues.iter().partition(|i| i.is_fatal());
+    for issue in others {
+        warn!("{issue}");
+    }
+    if !fatal.is_empty() {
+        let list = fatal
+            .iter()
+            .map(|i| format!("- {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        bail!("preflight checks failed:\n{list}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_paths() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("rust");
+        assert!(check_path(&dir).is_empty());
+
+        let issues = check_path(&root.path().join("it's rust"));
+        assert_eq!(issues, [PreflightIssue::UnsupportedChar('\'')]);
+
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join(InstallationRecord::FILENAME), "").unwrap();
+        let issues = check_path(&dir.join("nested"));
+        assert_eq!(issues, [PreflightIssue::NestedInstallation(dir.clone())]);
+        // installing into the same directory again is fine
+        assert!(check_path(&dir).is_empty());
+    }
+
+    #[test]
+    fn not_enough_space() {
+        let root = tempfile::tempdir().unwrap();
+        let issues = check_space(root.path(), u64::MAX);
+        assert!(matches!(
+            issues.as_slice(),
+            [PreflightIssue::InsufficientSpace { required, .. }] if required.0 == u64::MAX
+        ));
+        assert!(!issues[0].is_fatal());
+        assert!(check_space(root.path(), 0).is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+//! Resolve version requirements of cargo tools against a sparse registry index.
+
+use anyhow::{anyhow, bail, Context, Result};
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use url::Url;
+
+use crate::toolset_manifest::Proxy;
+use crate::utils::{self, DownloadOpt};
+
+/// A single line of a crate's index file, which describes one published version.
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// Get the requirement from a tool's version, or `None` if it's an exact version
+/// that can be passed to `cargo install --version` directly.
+pub(crate) fn version_req(version: &str) -> Option<VersionReq> {
+    if Version::parse(version).is_ok() {
+        return None;
+    }
+    VersionReq::parse(version).ok()
+}
+
+/// Check if the `installed` version of a tool differs from the `target` one in manifest.
+///
+/// The `target` could be a version requirement (such as `^0.9`), in which case the tool
+/// is only considered changed if `installed` no longer matches it, or if there is a newer
+/// matching version returned by `newest`.
+pub(crate) fn is_version_changed<F>(installed: &str, target: &str, newest: F) -> bool
+where
+    F: FnOnce() -> Option<String>,
+{
+    let Some(req) = version_req(target) else {
+        return installed != target;
+    };
+    let Ok(installed_ver) = Version::parse(installed) else {
+        return true;
+    };
+    if !req.matches(&installed_ver) {
+        return true;
+    }
+    newest().is_some_and(|newest| newest != installed)
+}
+
+/// Get the path of a crate's index file relative to the root of the index,
+/// such as `ca/rg/cargo-nextest`.
+fn index_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
+}
+
+/// Find the newest version in the content of an index file that matches `req`,
+/// yanked versions are ignored.
+fn newest_matching(index_content: &str, req: &VersionReq) -> Result<Option<Version>> {
+    let mut newest: Option<Version> = None;
+    for line in index_content.lines().filter(|l| !l.trim().is_empty()) {
+        let entry: IndexEntry =
+            serde_json::from_str(line).context("malformed entry in registry index")?;
+        if entry.yanked {
+            continue;
+        }
+        let version = Version::parse(&entry.vers)
+            .with_context(|| format!("invalid version '{}' in registry index", entry.vers))?;
+        if req.matches(&version) && newest.as_ref().map_or(true, |v| version > *v) {
+            newest = Some(version);
+        }
+    }
+    Ok(newest)
+}
+
+/// Resolve the newest version of crate `name` matching `req` from a sparse `registry`,
+/// such as `sparse+https://index.crates.io/`.
+pub(crate) fn resolve_version(
+    registry: &str,
+    name: &str,
+    req: &VersionReq,
+    proxy: Option<&Proxy>,
+) -> Result<Version> {
+    let Some(index) = registry.strip_prefix("sparse+") else {
+        bail!("unable to resolve version of '{name}' from registry '{registry}', only sparse registries are supported");
+    };
+    let index: Url = index
+        .parse()
+        .with_context(|| format!("invalid registry url '{registry}'"))?;
+    let url = utils::url_join(&index, &index_path(name))?;
+    let content = DownloadOpt::new(name)
+        .proxy(proxy.cloned())
+        .read(&url)
+        .with_context(|| format!("unable to read index of '{name}' from '{registry}'"))?;
+
+    newest_matching(&content, req)?
+        .ok_or_else(|| anyhow!("no version of '{name}' in '{registry}' matches '{req}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_version_is_not_a_requirement() {
+        assert!(version_req("0.9.85").is_none());
+        assert!(version_req("^0.9").is_some());
+        assert!(version_req("~1.2").is_some());
+        assert!(version_req("not a version").is_none());
+    }
+
+    #[test]
+    fn index_paths() {
+        assert_eq!(index_path("a"), "1/a");
+        assert_eq!(index_path("ab"), "2/ab");
+        assert_eq!(index_path("abc"), "3/a/abc");
+        assert_eq!(index_path("Cargo-Nextest"), "ca/rg/cargo-nextest");
+    }
+
+    #[test]
+    fn newest_matching_version() {
+        let content = r#"
+{"name":"foo","vers":"0.8.9","deps":[],"cksum":"","features":{},"yanked":false}
+{"name":"foo","vers":"0.9.1","deps":[],"cksum":"","features":{},"yanked":false}
+{"name":"foo","vers":"0.9.3","deps":[],"cksum":"","features":{},"yanked":true}
+{"name":"foo","vers":"0.9.2","deps":[],"cksum":"","features":{},"yanked":false}
+{"name":"foo","vers":"1.0.0","deps":[],"cksum":"","features":{},"yanked":false}
+"#;
+        let req = VersionReq::parse("^0.9").unwrap();
+        assert_eq!(
+            newest_matching(content, &req).unwrap(),
+            Some(Version::new(0, 9, 2))
+        );
+        let req = VersionReq::parse("^2").unwrap();
+        assert_eq!(newest_matching(content, &req).unwrap(), None);
+    }
+
+    #[test]
+    fn version_changed() {
+        assert!(!is_version_changed("0.9.2", "0.9.2", || None));
+        assert!(is_version_changed("0.9.2", "0.9.3", || None));
+        // no newer version matches the requirement
+        let newest = |ver: &str| Some(ver.to_string());
+        assert!(!is_version_changed("0.9.2", "^0.9", || newest("0.9.2")));
+        assert!(!is_version_changed("0.9.2", "^0.9", || None));
+        // a newer version matches the requirement
+        assert!(is_version_changed("0.9.2", "^0.9", || newest("0.9.5")));
+        // the installed version no longer matches the requirement
+        assert!(is_version_changed("0.9.2", "^1", || unreachable!()));
+    }
+}
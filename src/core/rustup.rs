@@ -45,6 +45,7 @@ impl ToolchainInstaller {
         rustup: &Path,
         manifest: &ToolsetManifest,
         components: Vec<&str>,
+        targets: &[String],
     ) -> Result<()> {
         // TODO: check local manifest.
         let version = manifest.rust.version.clone();
@@ -56,6 +57,10 @@ impl ToolchainInstaller {
             args.push("--component");
             args.extend(components);
         }
+        if !targets.is_empty() {
+            args.push("--target");
+            args.extend(targets.iter().map(|s| s.as_str()));
+        }
         let mut cmd = if let Some(local_server) = manifest.offline_dist_server()? {
             utils::cmd!([RUSTUP_DIST_SERVER=local_server.as_str()] rustup)
         } else if let Ok(dist_server) = std::env::var(RUSTUP_DIST_SERVER) {
@@ -74,12 +79,13 @@ impl ToolchainInstaller {
         utils::execute(cmd)
     }
 
-    /// Install rust toolchain & components via rustup.
+    /// Install rust toolchain & components & targets via rustup.
     pub(crate) fn install(
         &self,
         config: &InstallConfiguration,
         manifest: &ToolsetManifest,
         optional_components: &[String],
+        targets: &[String],
     ) -> Result<()> {
         let rustup = ensure_rustup(config, manifest, self.insecure)?;
 
@@ -90,7 +96,7 @@ impl ToolchainInstaller {
             .map(|s| s.as_str())
             .chain(optional_components.iter().map(|s| s.as_str()))
             .collect();
-        self.install_toolchain_via_rustup(&rustup, manifest, components_to_install)?;
+        self.install_toolchain_via_rustup(&rustup, manifest, components_to_install, targets)?;
 
         // Remove the `rustup` uninstall entry on windows, because we don't want users to
         // accidently uninstall `rustup` thus removing the tools installed by this program.
@@ -107,11 +113,40 @@ impl ToolchainInstaller {
         &self,
         config: &InstallConfiguration,
         manifest: &ToolsetManifest,
+        components: &[String],
+        targets: &[String],
     ) -> Result<()> {
         let rustup = ensure_rustup(config, manifest, self.insecure)?;
         let tc_ver = manifest.rust_version();
 
-        utils::run!(&rustup, "toolchain", "add", tc_ver)
+        let mut cmd = utils::cmd!(&rustup, "toolchain", "add", tc_ver);
+        if !components.is_empty() {
+            cmd.arg("--component").args(components);
+        }
+        if !targets.is_empty() {
+            cmd.arg("--target").args(targets);
+        }
+        utils::execute(cmd)
+    }
+
+    /// Roll rust toolchain back to the version of `manifest` by invoking `rustup toolchain add`
+    /// and `rustup default`, then remove the `replaced` toolchain.
+    pub(crate) fn rollback(
+        &self,
+        config: &InstallConfiguration,
+        manifest: &ToolsetManifest,
+        components: &[String],
+        targets: &[String],
+        replaced: &str,
+    ) -> Result<()> {
+        self.update(config, manifest, components, targets)?;
+        let rustup = config.cargo_bin().join(RUSTUP);
+        let tc_ver = manifest.rust_version();
+        utils::run!(&rustup, "default", tc_ver)?;
+        if replaced != tc_ver {
+            utils::run!(&rustup, "toolchain", "uninstall", replaced)?;
+        }
+        Ok(())
     }
 
     // Rustup self uninstall all the components and toolchains.
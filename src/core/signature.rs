@@ -0,0 +1,138 @@
+//! Verification of detached [minisign](https://jedisct1.github.io/minisign/) signatures,
+//! used to make sure the manifests we downloaded was actually published by the vendor.
+
+use anyhow::{anyhow, bail, Context, Result};
+use log::debug;
+use minisign_verify::{PublicKey, Signature};
+use url::Url;
+
+use crate::utils;
+
+/// The file extension of detached signature files.
+pub(crate) const SIGNATURE_EXT: &str = "sig";
+
+/// Get the content of baked-in trusted public keys as `str`.
+fn baked_in_trusted_keys_raw() -> &'static str {
+    include_str!("../../resources/trusted_keys.pub")
+}
+
+/// Parse a list of public keys from raw string.
+///
+/// Empty lines, comments (`#`), and the `untrusted comment` lines of `minisign.pub` are skipped.
+fn parse_keys(raw: &str) -> Result<Vec<PublicKey>> {
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty() && !line.starts_with('#') && !line.starts_with("untrusted comment:")
+        })
+        .map(|line| {
+            PublicKey::from_base64(line)
+                .map_err(|e| anyhow!("invalid trusted public key '{line}': {e}"))
+        })
+        .collect()
+}
+
+/// Get the list of trusted public keys, which was baked in at compile time.
+fn trusted_keys() -> Result<Vec<PublicKey>> {
+    parse_keys(baked_in_trusted_keys_raw())
+}
+
+/// Verify `content` with a raw detached `signature` using any of the given `keys`.
+fn verify_with_keys(name: &str, content: &[u8], signature: &str, keys: &[PublicKey]) -> Result<()> {
+    let signature = Signature::decode(signature)
+        .map_err(|e| anyhow!("invalid signature format for {name}: {e}"))?;
+    if keys
+        .iter()
+        .any(|key| key.verify(content, &signature, true).is_ok())
+    {
+        Ok(())
+    } else {
+        bail!("{name} is not signed by any of the trusted keys");
+    }
+}
+
+/// Download the detached signature of a file located at `url` (`<url>.sig`),
+/// then verify the `content` of that file with it.
+///
+/// Verification will be skipped if there are no trusted keys configured.
+///
+/// # Error
+/// Return `Err` if the signature cannot be fetched, or it does not match the `content`.
+pub(crate) fn verify_downloaded(
+    name: &str,
+    url: &Url,
+    content: &[u8],
+    insecure: bool,
+) -> Result<()> {
+    let keys = trusted_keys()?;
+    if keys.is_empty() {
+        debug!("no trusted keys configured, skipping signature verification of {name}");
+        return Ok(());
+    }
+
+    let sig_url = signature_url(url)?;
+    let signature = utils::DownloadOpt::new(format!("{name} signature"))
+        .insecure(insecure)
+        .read(&sig_url)
+        .with_context(|| format!("unable to fetch the signature of {name} from '{sig_url}'"))?;
+
+    verify_with_keys(name, content, &signature, &keys)
+        .with_context(|| format!("refusing to use {name} downloaded from '{url}'"))
+}
+
+/// Get the url of the detached signature file, which is the original url plus `.sig`.
+fn signature_url(url: &Url) -> Result<Url> {
+    if url.path().ends_with('/') {
+        bail!("'{url}' doesn't appear to be a path to file");
+    }
+    let mut sig_url = url.clone();
+    sig_url.set_path(&format!("{}.{SIGNATURE_EXT}", url.path()));
+    Ok(sig_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vector from `minisign-verify`, the signature is signed for content `test`.
+    const PUBLIC_KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1556193335\tfile:test
+y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==";
+
+    #[test]
+    fn parse_key_file() {
+        let input =
+            format!("# some comments\n\nuntrusted comment: minisign public key\n{PUBLIC_KEY}\n");
+        assert_eq!(parse_keys(&input).unwrap().len(), 1);
+        assert!(parse_keys("not a key").is_err());
+    }
+
+    #[test]
+    fn baked_in_keys_are_valid() {
+        assert!(trusted_keys().is_ok());
+    }
+
+    #[test]
+    fn verify_signature() {
+        let keys = parse_keys(PUBLIC_KEY).unwrap();
+        assert!(verify_with_keys("test", b"test", SIGNATURE, &keys).is_ok());
+        assert!(verify_with_keys("test", b"tampered", SIGNATURE, &keys).is_err());
+        assert!(verify_with_keys("test", b"test", SIGNATURE, &[]).is_err());
+    }
+
+    #[test]
+    fn signature_urls() {
+        let url = Url::parse("https://example.com/dist/distribution-manifest.toml").unwrap();
+        assert_eq!(
+            signature_url(&url).unwrap().as_str(),
+            "https://example.com/dist/distribution-manifest.toml.sig"
+        );
+        let url = Url::parse("file:///path/to/manifest.toml").unwrap();
+        assert_eq!(
+            signature_url(&url).unwrap().as_str(),
+            "file:///path/to/manifest.toml.sig"
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! A snapshot of the installed toolkit, which is taken before updating it,
+//! so that the toolkit could be rolled back to the previous version if the update broke something.
+//!
+//! The snapshot contains a copy of the installed manifest and installation record,
+//! and optionally a copy of the files of the tools that were replaced by the update.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use log::debug;
+
+use super::parser::fingerprint::InstallationRecord;
+use super::parser::toolset_manifest::ToolsetManifest;
+use super::parser::TomlParser;
+use crate::utils;
+
+pub struct Snapshot {
+    dir: PathBuf,
+}
+
+impl Snapshot {
+    const DIRNAME: &str = ".snapshot";
+    const TOOLS_DIRNAME: &str = "tools";
+
+    /// Take a snapshot of the toolkit installed in `record`'s root, replacing the previous one.
+    ///
+    /// The files of each tool in `tools` are copied as well, so that those tools could be
+    /// restored without downloading them again.
+    pub(crate) fn take(record: &InstallationRecord, tools: &[&str]) -> Result<Self> {
+        let root = &record.root;
+        let snapshot = Self {
+            dir: root.join(Self::DIRNAME),
+        };
+        debug!(
+            "taking a snapshot of the toolkit into '{}'",
+            snapshot.dir.display()
+        );
+        utils::remove(&snapshot.dir)?;
+        utils::ensure_dir(&snapshot.dir)?;
+
+        utils::copy_into(root.join(ToolsetManifest::FILENAME), &snapshot.dir)?;
+        utils::copy_into(root.join(InstallationRecord::FILENAME), &snapshot.dir)?;
+
+        for name in tools {
+            let Some(tool) = record.tools.get(*name) else {
+                continue;
+            };
+            let tool_dir = snapshot.dir.join(Self::TOOLS_DIRNAME).join(name);
+            utils::ensure_dir(&tool_dir)?;
+            for (idx, path) in tool.paths.iter().enumerate() {
+                if path.exists() {
+                    utils::copy_as(path, tool_dir.join(idx.to_string())).with_context(|| {
+                        format!("unable to copy '{}' into snapshot", path.display())
+                    })?;
+                }
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Get the snapshot taken in `root`, if there is one.
+    pub fn load(root: &Path) -> Option<Self> {
+        let dir = root.join(Self::DIRNAME);
+        dir.join(InstallationRecord::FILENAME)
+            .is_file()
+            .then_some(Self { dir })
+    }
+
+    pub fn manifest(&self) -> Result<ToolsetManifest> {
+        ToolsetManifest::load(self.dir.join(ToolsetManifest::FILENAME))
+    }
+
+    pub fn record(&self) -> Result<InstallationRecord> {
+        let raw = utils::read_to_string(
+            "snapshot record",
+            self.dir.join(InstallationRecord::FILENAME),
+        )?;
+        InstallationRecord::from_str(&raw)
+    }
+
+    /// Get the copies of the paths of tool `name`, along with the path that each of them
+    /// should be restored to, return `None` if its files weren't copied.
+    pub(crate) fn tool_files(
+        &self,
+        name: &str,
+        paths: &[PathBuf],
+    ) -> Option<Vec<(PathBuf, PathBuf)>> {
+        let tool_dir = self.dir.join(Self::TOOLS_DIRNAME).join(name);
+        if !tool_dir.is_dir() {
+            return None;
+        }
+        let files = paths
+            .iter()
+            .enumerate()
+            .map(|(idx, path)| (tool_dir.join(idx.to_string()), path.clone()))
+            .filter(|(copy, _)| copy.exists())
+            .collect();
+        Some(files)
+    }
+
+    /// Remove the snapshot, which should be done after it was restored.
+    pub(crate) fn remove(self) -> Result<()> {
+        utils::remove(&self.dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_and_load_snapshot() {
+        let root = tempfile::tempdir().unwrap();
+        let tool_dir = root.path().join("tools").join("aaa");
+        utils::ensure_dir(&tool_dir).unwrap();
+        utils::write_file(tool_dir.join("aaa"), "binary", false).unwrap();
+        utils::write_file(
+            root.path().join(ToolsetManifest::FILENAME),
+            "[rust]\nversion = \"1.80.0\"",
+            false,
+        )
+        .unwrap();
+        let record_raw = format!(
+            "version = \"0.1.0\"\nroot = '{}'\n\n[tools]\naaa = {{ kind = \"custom\", paths = ['{}'] }}\nbbb = {{ kind = \"custom\", paths = [] }}\n",
+            root.path().display(),
+            tool_dir.display()
+        );
+        utils::write_file(
+            root.path().join(InstallationRecord::FILENAME),
+            &record_raw,
+            false,
+        )
+        .unwrap();
+        let record = InstallationRecord::from_str(&record_raw).unwrap();
+
+        assert!(Snapshot::load(root.path()).is_none());
+        Snapshot::take(&record, &["aaa"]).unwrap();
+        // files changed by an update should not affect the snapshot
+        utils::write_file(tool_dir.join("aaa"), "new binary", false).unwrap();
+
+        let snapshot = Snapshot::load(root.path()).unwrap();
+        assert_eq!(snapshot.manifest().unwrap().rust_version(), "1.80.0");
+        assert_eq!(snapshot.record().unwrap().version.as_deref(), Some("0.1.0"));
+
+        let files = snapshot
+            .tool_files("aaa", &record.tools["aaa"].paths)
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].1, tool_dir);
+        let saved = utils::read_to_string("test", files[0].0.join("aaa")).unwrap();
+        assert_eq!(saved, "binary\n");
+        assert!(snapshot.tool_files("bbb", &[]).is_none());
+
+        snapshot.remove().unwrap();
+        assert!(Snapshot::load(root.path()).is_none());
+    }
+}
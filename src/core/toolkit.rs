@@ -11,6 +11,7 @@ use serde::Serialize;
 use url::Url;
 
 use super::parser::dist_manifest::DistPackage;
+use super::{signature, update};
 
 /// A cached installed [`Toolkit`] struct to prevent the program doing
 /// excessive IO operations as in [`installed`](Toolkit::installed).
@@ -30,6 +31,9 @@ pub struct Toolkit {
     #[serde(rename = "manifestURL")]
     pub manifest_url: Option<String>,
     pub components: Vec<components::Component>,
+    /// The minimum version of manager that is able to install this toolkit.
+    #[serde(skip)]
+    min_manager_version: Option<String>,
 }
 
 impl PartialEq for Toolkit {
@@ -67,6 +71,7 @@ impl Toolkit {
             info: None,
             manifest_url: None,
             components,
+            min_manager_version: None,
         };
 
         if let Some(existing) = INSTALLED_KIT.get() {
@@ -92,6 +97,7 @@ impl From<DistPackage> for Toolkit {
             info: value.info,
             manifest_url: Some(value.manifest_url.to_string()),
             components: vec![],
+            min_manager_version: value.min_manager_version,
         }
     }
 }
@@ -120,6 +126,8 @@ fn toolkits_from_server(insecure: bool) -> Result<&'static [Toolkit]> {
         .insecure(insecure)
         .download_file(&dist_m_url, dist_m_file.path(), false)?;
     debug!("distribution manifest file successfully downloaded!");
+    let content = std::fs::read(dist_m_file.path())?;
+    signature::verify_downloaded("distribution manifest", &dist_m_url, &content, insecure)?;
 
     // load dist "pacakges" then convert them into `toolkit`s
     let packages = DistManifest::load(dist_m_file.path())?.packages;
@@ -132,7 +140,12 @@ fn toolkits_from_server(insecure: bool) -> Result<&'static [Toolkit]> {
     Ok(cached)
 }
 
-/// Return a list of all toolkits that are not currently installed.
+/// Return a list of all toolkits that are not currently installed,
+/// the ones requiring a newer version of this manager are left out.
+///
+/// # Error
+/// Fails with a [`ManagerTooOld`](update::ManagerTooOld) error if every one of them
+/// requires a newer version of this manager.
 pub fn installable_toolkits(reload_cache: bool, insecure: bool) -> Result<Vec<&'static Toolkit>> {
     let all_toolkits = toolkits_from_server(insecure)?;
     let installable = if let Some(installed) = Toolkit::installed(reload_cache)? {
@@ -141,9 +154,37 @@ pub fn installable_toolkits(reload_cache: bool, insecure: bool) -> Result<Vec<&'
             .filter(|tk| *tk != &*installed.lock().unwrap())
             .collect()
     } else {
-        all_toolkits.iter().collect()
+        all_toolkits.iter().collect::<Vec<_>>()
     };
-    Ok(installable)
+    compatible_toolkits(installable)
+}
+
+/// Filter out the toolkits that require a newer version of this manager,
+/// fails if there's none left.
+fn compatible_toolkits(toolkits: Vec<&Toolkit>) -> Result<Vec<&Toolkit>> {
+    let mut compatible = vec![];
+    let mut incompatible_err = None;
+    for toolkit in toolkits {
+        match update::ensure_manager_version(toolkit.min_manager_version.as_deref()) {
+            Ok(()) => compatible.push(toolkit),
+            Err(e) => {
+                info!(
+                    "{}",
+                    t!(
+                        "toolkit_incompatible",
+                        name = toolkit.name,
+                        version = toolkit.version,
+                        reason = e
+                    )
+                );
+                incompatible_err.get_or_insert(e);
+            }
+        }
+    }
+    match incompatible_err {
+        Some(e) if compatible.is_empty() => Err(e),
+        _ => Ok(compatible),
+    }
 }
 
 /// Return the latest available toolkit if it's not already installed.
@@ -179,6 +220,7 @@ pub fn latest_installable_toolkit(
         let target_version: Version = target_ver.parse()?;
 
         if target_version > cur_version {
+            update::ensure_manager_version(maybe_latest.min_manager_version.as_deref())?;
             Ok(Some(maybe_latest))
         } else {
             info!(
@@ -192,6 +234,40 @@ pub fn latest_installable_toolkit(
             Ok(None)
         }
     } else {
+        if let Some(latest) = all_toolkits.first() {
+            update::ensure_manager_version(latest.min_manager_version.as_deref())?;
+        }
         Ok(all_toolkits.first())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toolkit(version: &str, min_manager_version: Option<&str>) -> Toolkit {
+        Toolkit {
+            name: "Toolkit".into(),
+            version: version.into(),
+            desc: None,
+            info: None,
+            manifest_url: None,
+            components: vec![],
+            min_manager_version: min_manager_version.map(ToOwned::to_owned),
+        }
+    }
+
+    #[test]
+    fn leave_out_incompatible_toolkits() {
+        let newer = toolkit("2.0.0", Some("999.0.0"));
+        let older = toolkit("1.0.0", Some("0.0.1"));
+        let oldest = toolkit("0.1.0", None);
+
+        let compatible = compatible_toolkits(vec![&newer, &older, &oldest]).unwrap();
+        assert_eq!(compatible, [&older, &oldest]);
+
+        let err = compatible_toolkits(vec![&newer]).unwrap_err();
+        assert!(update::ManagerTooOld::find_in(&err).is_some());
+        assert!(compatible_toolkits(vec![]).unwrap().is_empty());
+    }
+}
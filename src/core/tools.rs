@@ -4,13 +4,13 @@ use std::{
     str::FromStr,
 };
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
 use super::{
     directories::RimDir, parser::fingerprint::ToolRecord, uninstall::UninstallConfiguration,
-    GlobalOpts, PathExt, CARGO_HOME,
+    GlobalOpts, PathExt, CARGO_HOME, RUSTUP_HOME,
 };
 use crate::{core::custom_instructions, setter, utils, InstallConfiguration};
 
@@ -220,6 +220,42 @@ fn cargo_install_or_uninstall(op: &str, args: &[&str], cargo_home: &Path) -> Res
     utils::execute(cmd)
 }
 
+/// Run the hook `commands` of a tool at certain `stage` (such as `post-install`).
+///
+/// Commands are executed with `CARGO_HOME`, `RUSTUP_HOME`, `RIM_INSTALL_DIR` and
+/// `RIM_TOOL_DIR` environment variables set.
+pub(crate) fn run_hooks<T: RimDir>(
+    name: &str,
+    stage: &str,
+    commands: &[Vec<String>],
+    config: &T,
+) -> Result<()> {
+    let tool_dir = config.tools_dir().join(name);
+    for command in commands {
+        let Some((program, args)) = command.split_first() else {
+            continue;
+        };
+        info!(
+            "{}",
+            t!(
+                "running_hook",
+                name = name,
+                stage = stage,
+                cmd = command.join(" ")
+            )
+        );
+        let mut cmd = utils::cmd!([
+            CARGO_HOME=config.cargo_home(),
+            RUSTUP_HOME=config.rustup_home(),
+            "RIM_INSTALL_DIR"=config.install_dir(),
+            "RIM_TOOL_DIR"=&tool_dir
+        ] program);
+        cmd.args(args);
+        utils::execute(cmd).with_context(|| format!("failed to run {stage} hook of '{name}'"))?;
+    }
+    Ok(())
+}
+
 /// Move one path (file/dir) to a new folder with `name` under tools dir.
 fn move_to_tools(config: &InstallConfiguration, name: &str, path: &Path) -> Result<PathBuf> {
     let dir = config.tools_dir().join(name);
@@ -1,23 +1,29 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use indexmap::IndexMap;
 use log::{info, warn};
 
 use super::{
     directories::RimDir,
+    history::{HistoryOperation, HistoryTracker},
+    os::remove_env_vars,
     parser::fingerprint::{installed_tools_fresh, InstallationRecord, ToolRecord},
+    parser::installed_files::InstalledFiles,
+    parser::toolset_manifest::Hooks,
+    parser::TomlParser,
     rustup::ToolchainInstaller,
-    tools::ToolKind,
+    tools::{run_hooks, ToolKind},
 };
-use crate::{core::tools::Tool, utils::Progress};
+use crate::{core::tools::Tool, setter, utils::Progress};
 
 /// Contains definition of uninstallation steps.
 pub(crate) trait Uninstallation {
     /// Remove persistent environment variables for `rustup`.
     ///
     /// This will remove persistent environment variables including
-    /// `RUSTUP_DIST_SERVER`, `RUSTUP_UPDATE_ROOT`, `CARGO_HOME`, `RUSTUP_HOME`.
+    /// `RUSTUP_DIST_SERVER`, `RUSTUP_UPDATE_ROOT`, `CARGO_HOME`, `RUSTUP_HOME`,
+    /// and the ones set for tools (if any of them are left).
     fn remove_rustup_env_vars(&self) -> Result<()>;
     /// The last step of uninstallation, this will remove the binary itself, along with
     /// the folder it's in.
@@ -31,6 +37,8 @@ pub struct UninstallConfiguration<'a> {
     pub(crate) install_dir: PathBuf,
     pub(crate) install_record: InstallationRecord,
     pub(crate) progress_indicator: Option<Progress<'a>>,
+    /// Whether to run the hooks of tools, which should be confirmed by the user.
+    run_hooks: bool,
 }
 
 impl RimDir for UninstallConfiguration<'_> {
@@ -42,11 +50,47 @@ impl RimDir for UninstallConfiguration<'_> {
 impl<'a> UninstallConfiguration<'a> {
     pub fn init(progress: Option<Progress<'a>>) -> Result<Self> {
         let install_record = InstallationRecord::load_from_install_dir()?;
-        Ok(Self {
+        Ok(Self::with_record(install_record, progress))
+    }
+
+    /// Same as [`init`](Self::init), but for the installation in `install_dir`,
+    /// rather than the one this program is in.
+    pub(crate) fn init_in(install_dir: &Path) -> Result<Self> {
+        Ok(Self::with_record(
+            InstallationRecord::load(install_dir)?,
+            None,
+        ))
+    }
+
+    fn with_record(install_record: InstallationRecord, progress: Option<Progress<'a>>) -> Self {
+        Self {
             install_dir: install_record.root.clone(),
             install_record,
             progress_indicator: progress,
-        })
+            run_hooks: false,
+        }
+    }
+
+    setter!(run_hooks(self, bool));
+
+    /// Describe the uninstallation hooks of the given tools (or all tools if `None`)
+    /// in human readable lines.
+    pub fn hook_descriptions(&self, names: Option<&[String]>) -> Vec<String> {
+        self.install_record
+            .tools
+            .iter()
+            .filter(|(name, _)| names.map_or(true, |names| names.contains(name)))
+            .flat_map(|(name, record)| {
+                let hooks = &record.hooks;
+                Hooks::describe(name, "pre-uninstall", &hooks.pre_uninstall)
+                    .into_iter()
+                    .chain(Hooks::describe(
+                        name,
+                        "post-uninstall",
+                        &hooks.post_uninstall,
+                    ))
+            })
+            .collect()
     }
 
     pub(crate) fn inc_progress(&self, val: f32) -> Result<()> {
@@ -57,6 +101,17 @@ impl<'a> UninstallConfiguration<'a> {
     }
 
     pub fn uninstall(mut self, remove_self: bool) -> Result<()> {
+        // there's no history to keep if the whole installation is gone
+        let history = (!remove_self)
+            .then(|| HistoryTracker::start(HistoryOperation::Uninstall, &self.install_record));
+        let result = self.uninstall_(remove_self);
+        if let Some(history) = history {
+            history.finish(&self.install_record, &result);
+        }
+        result
+    }
+
+    fn uninstall_(&mut self, remove_self: bool) -> Result<()> {
         // remove all tools.
         info!("{}", t!("uninstalling_third_party_tools"));
         self.remove_tools(installed_tools_fresh(&self.install_dir)?, 40.0)?;
@@ -64,7 +119,7 @@ impl<'a> UninstallConfiguration<'a> {
         // Remove rust toolchain via rustup.
         if self.install_record.rust.is_some() {
             info!("{}", t!("uninstalling_rust_toolchain"));
-            ToolchainInstaller::init().remove_self(&self)?;
+            ToolchainInstaller::init().remove_self(self)?;
             self.install_record.remove_rust_record();
             self.install_record.write()?;
         }
@@ -88,8 +143,65 @@ impl<'a> UninstallConfiguration<'a> {
         Ok(())
     }
 
+    /// Uninstall the given tools only, leaving the toolchain and other tools untouched.
+    ///
+    /// # Errors
+    /// Return `Err` if any of the tools is not installed, or it is still required by other
+    /// installed tools that are not being uninstalled together.
+    pub fn uninstall_tools(mut self, names: &[String]) -> Result<()> {
+        let history =
+            HistoryTracker::start(HistoryOperation::UninstallComponents, &self.install_record);
+        let result = self.uninstall_tools_(names);
+        history.finish(&self.install_record, &result);
+        result
+    }
+
+    fn uninstall_tools_(&mut self, names: &[String]) -> Result<()> {
+        let is_removing = |name: &str| names.iter().any(|n| n == name);
+        for name in names {
+            if !self.install_record.tools.contains_key(name) {
+                bail!("'{name}' is not an installed tool");
+            }
+            let dependents = self
+                .install_record
+                .dependents_of(name)
+                .filter(|dependent| !is_removing(dependent))
+                .collect::<Vec<_>>();
+            if !dependents.is_empty() {
+                bail!(
+                    "unable to uninstall '{name}' because it is required by: {}",
+                    dependents.join(", ")
+                );
+            }
+        }
+
+        let tools = installed_tools_fresh(&self.install_dir)?
+            .into_iter()
+            .filter(|(name, _)| is_removing(name))
+            .collect();
+        info!("{}", t!("uninstalling_third_party_tools"));
+        self.remove_tools(tools, 100.0)
+    }
+
+    /// Run the hooks of a tool if allowed, failures are not fatal since
+    /// the tool is being removed anyway.
+    fn run_hooks_or_warn(&self, name: &str, stage: &str, commands: &[Vec<String>]) {
+        if commands.is_empty() {
+            return;
+        }
+        if !self.run_hooks {
+            warn!("{}", t!("hooks_skipped", name = name, stage = stage));
+        } else if let Err(e) = run_hooks(name, stage, commands, self) {
+            warn!("{e:?}");
+        }
+    }
+
     /// Uninstall all tools
-    fn remove_tools(&mut self, tools: IndexMap<String, ToolRecord>, weight: f32) -> Result<()> {
+    pub(crate) fn remove_tools(
+        &mut self,
+        tools: IndexMap<String, ToolRecord>,
+        weight: f32,
+    ) -> Result<()> {
         let mut tools_to_uninstall = vec![];
         for (name, tool_detail) in &tools {
             let kind = tool_detail.tool_kind();
@@ -122,7 +234,7 @@ impl<'a> UninstallConfiguration<'a> {
                 }
                 _ => Tool::new(name.into(), kind).path(tool_detail.paths.clone()),
             };
-            tools_to_uninstall.push(tool);
+            tools_to_uninstall.push((tool, tool_detail));
         }
 
         if tools_to_uninstall.is_empty() {
@@ -130,10 +242,12 @@ impl<'a> UninstallConfiguration<'a> {
         }
         let progress_dt = weight / tools_to_uninstall.len() as f32;
 
-        tools_to_uninstall.sort_by(|a, b| b.kind.cmp(&a.kind));
+        tools_to_uninstall.sort_by(|(a, _), (b, _)| b.kind.cmp(&a.kind));
 
-        for tool in tools_to_uninstall {
+        for (tool, tool_detail) in tools_to_uninstall {
             info!("{}", t!("uninstalling_for", name = tool.name()));
+            let hooks = &tool_detail.hooks;
+            self.run_hooks_or_warn(tool.name(), "pre-uninstall", &hooks.pre_uninstall);
             if tool.uninstall(self).is_err() {
                 info!(
                     "{}: {}",
@@ -141,8 +255,14 @@ impl<'a> UninstallConfiguration<'a> {
                     t!("maybe_uninstalled_already")
                 );
             }
+            self.run_hooks_or_warn(tool.name(), "post-uninstall", &hooks.post_uninstall);
+            let env = &tool_detail.env;
+            remove_env_vars(&env.keys().map(String::as_str).collect::<Vec<_>>())?;
             self.install_record.remove_tool_record(tool.name());
             self.install_record.write()?;
+            let mut files = InstalledFiles::load_from_root(&self.install_dir)?;
+            files.remove_tool(tool.name());
+            files.write(&self.install_dir)?;
             self.inc_progress(progress_dt)?;
         }
 
@@ -161,6 +161,53 @@ pub fn check_self_update(insecure: bool) -> SelfUpdateKind<'static> {
     }
 }
 
+/// The error of a toolkit or a toolset manifest requiring a newer version of this manager,
+/// which could be solved by a [self update](UpdateOpt::self_update).
+#[derive(Debug)]
+pub struct ManagerTooOld {
+    pub required: Version,
+}
+
+impl std::fmt::Display for ManagerTooOld {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "manager version {} or newer is required, but the current version is {}",
+            self.required,
+            env!("CARGO_PKG_VERSION")
+        )
+    }
+}
+
+impl std::error::Error for ManagerTooOld {}
+
+impl ManagerTooOld {
+    /// Find this error in the chain of `err`, if it was caused by this.
+    pub fn find_in(err: &anyhow::Error) -> Option<&Self> {
+        err.chain().find_map(|e| e.downcast_ref::<Self>())
+    }
+}
+
+/// Make sure this manager is not older than the `required` version (`min-manager-version`),
+/// return a [`ManagerTooOld`] error otherwise.
+pub(crate) fn ensure_manager_version(required: Option<&str>) -> Result<()> {
+    let Some(required) = required else {
+        return Ok(());
+    };
+    // safe to unwrap, otherwise cargo would fails the build
+    let current = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+    ensure_version_(required, &current)
+}
+
+fn ensure_version_(required: &str, current: &Version) -> Result<()> {
+    let required = Version::parse(required)
+        .with_context(|| format!("invalid minimum manager version '{required}'"))?;
+    if current < &required {
+        return Err(ManagerTooOld { required }.into());
+    }
+    Ok(())
+}
+
 fn parse_download_url(source_path: &str) -> Result<Url> {
     let base_obs_server: Url = env::var("RIM_DIST_SERVER")
         .as_deref()
@@ -191,4 +238,18 @@ mod tests {
         compare!("0.1.0-alpha.1" < "0.1.0-alpha.2");
         compare!("1.0.0" == "1.0.0");
     }
+
+    #[test]
+    fn minimum_manager_version() {
+        use super::{ensure_version_, ManagerTooOld};
+
+        let current = semver::Version::new(0, 3, 0);
+        assert!(ensure_version_("0.2.0", &current).is_ok());
+        assert!(ensure_version_("0.3.0", &current).is_ok());
+        assert!(ensure_version_("not a version", &current).is_err());
+
+        let err = ensure_version_("0.3.1", &current).unwrap_err();
+        let too_old = ManagerTooOld::find_in(&err).unwrap();
+        assert_eq!(too_old.required, semver::Version::new(0, 3, 1));
+    }
 }
@@ -10,9 +10,11 @@ pub mod utils;
 
 // Exports
 pub use core::install::{default_install_dir, EnvConfig, InstallConfiguration};
-pub use core::parser::{fingerprint, get_installed_dir, toolset_manifest};
+pub use core::parser::{
+    fingerprint, get_installed_dir, installed_files, lockfile, toolset_manifest,
+};
 pub use core::try_it::try_it;
 pub use core::uninstall::UninstallConfiguration;
-pub use core::{components, toolkit, update, Language};
+pub use core::{components, history, preflight, toolkit, update, Language};
 
 i18n!("locales", fallback = "en");
@@ -0,0 +1,113 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256, Sha512};
+
+/// Supported hash algorithms for verifying the integrity of a downloaded (or local) package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sha256 => write!(f, "sha256"),
+            Self::Sha512 => write!(f, "sha512"),
+        }
+    }
+}
+
+/// Calculate the digest of a file with the given algorithm, and return it as lowercase hex string.
+pub fn file_digest<P: AsRef<Path>>(path: P, algorithm: HashAlgorithm) -> Result<String> {
+    let path = path.as_ref();
+    let mut file = File::open(path)
+        .with_context(|| format!("unable to open '{}' to calculate hash", path.display()))?;
+
+    let digest = match algorithm {
+        HashAlgorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            io::copy(&mut file, &mut hasher)?;
+            format!("{:x}", hasher.finalize())
+        }
+        HashAlgorithm::Sha512 => {
+            let mut hasher = Sha512::new();
+            io::copy(&mut file, &mut hasher)?;
+            format!("{:x}", hasher.finalize())
+        }
+    };
+    Ok(digest)
+}
+
+/// Make sure the digest of the file located in `path` matches the `expected` one.
+///
+/// The comparison is case-insensitive, `name` is only used for error message.
+///
+/// # Error
+/// Return `Err` if `path` is not a file, or the digest doesn't match.
+pub fn verify_checksum<P: AsRef<Path>>(
+    name: &str,
+    path: P,
+    algorithm: HashAlgorithm,
+    expected: &str,
+) -> Result<()> {
+    let path = path.as_ref();
+    if !path.is_file() {
+        bail!(
+            "unable to verify {algorithm} checksum of '{name}': '{}' is not a file",
+            path.display()
+        );
+    }
+
+    let actual = file_digest(path, algorithm)?;
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        bail!(
+            "{algorithm} checksum mismatch for '{name}' ({}): expected '{}', got '{actual}'",
+            path.display(),
+            expected.trim(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &[u8] = b"hello world";
+    const SHA256_OF_CONTENT: &str =
+        "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+
+    #[test]
+    fn sha256_digest() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), CONTENT).unwrap();
+
+        let digest = file_digest(file.path(), HashAlgorithm::Sha256).unwrap();
+        assert_eq!(digest, SHA256_OF_CONTENT);
+    }
+
+    #[test]
+    fn verify_matched_and_mismatched() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), CONTENT).unwrap();
+
+        assert!(verify_checksum(
+            "foo",
+            file.path(),
+            HashAlgorithm::Sha256,
+            &SHA256_OF_CONTENT.to_uppercase()
+        )
+        .is_ok());
+
+        let err = verify_checksum("foo", file.path(), HashAlgorithm::Sha256, "abc").unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("'foo'"));
+        assert!(msg.contains("expected 'abc'"));
+        assert!(msg.contains(SHA256_OF_CONTENT));
+    }
+}
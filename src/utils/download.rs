@@ -103,6 +103,33 @@ impl DownloadOpt<ProgressBar> {
             );
         }
     }
+    /// Consume self, and get the size of the file at `url` without downloading it,
+    /// which is the `Content-Length` responded by the server, or the size of a local file.
+    ///
+    /// Return `None` if the server does not tell the size.
+    pub fn content_length(self, url: &Url) -> Result<Option<u64>> {
+        if url.scheme() == "file" {
+            let path = url
+                .to_file_path()
+                .map_err(|_| anyhow!("unable to convert to file path for url '{url}'"))?;
+            return Ok(Some(fs::metadata(path)?.len()));
+        }
+
+        let resp = self
+            .client()?
+            .head(url.as_ref())
+            .send()
+            .with_context(|| format!("failed to receive surver response from '{url}'"))?;
+        if !resp.status().is_success() {
+            return Ok(None);
+        }
+        Ok(resp
+            .headers()
+            .get(reqwest::header::CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok())
+            .and_then(|len| len.parse().ok()))
+    }
+
     /// Consume self, and download from given `Url` to `Path`.
     // TODO: make local file download fancier
     pub fn download_file(self, url: &Url, path: &Path, resume: bool) -> Result<()> {
@@ -330,3 +330,64 @@ pub fn make_temp_file(prefix: &str, root: Option<&Path>) -> Result<NamedTempFile
 pub fn extension_str(path: &Path) -> Option<&str> {
     path.extension().and_then(|ext| ext.to_str())
 }
+
+/// Get the closest ancestor of `path` (including itself) that exists.
+pub fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| p.exists())
+}
+
+/// Get the available space (in bytes) of the volume containing `path`,
+/// which does not need to exist yet.
+#[cfg(not(windows))]
+pub fn available_space(path: &Path) -> Result<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = existing_ancestor(path)
+        .ok_or_else(|| anyhow!("unable to find the volume of '{}'", path.display()))?;
+    let c_path = CString::new(dir.as_os_str().as_bytes())?;
+    // SAFETY: `c_path` is a valid nul-terminated string, and `stat` is only read after
+    // `statvfs` succeeded.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("unable to get the free space of '{}'", dir.display()));
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Get the available space (in bytes) of the volume containing `path`,
+/// which does not need to exist yet.
+#[cfg(windows)]
+pub fn available_space(path: &Path) -> Result<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::fileapi::GetDiskFreeSpaceExW;
+    use winapi::um::winnt::ULARGE_INTEGER;
+
+    let dir = existing_ancestor(path)
+        .ok_or_else(|| anyhow!("unable to find the volume of '{}'", path.display()))?;
+    let wide: Vec<u16> = dir.as_os_str().encode_wide().chain(Some(0)).collect();
+    // SAFETY: `wide` is a valid nul-terminated wide string, and `available` is only read after
+    // `GetDiskFreeSpaceExW` succeeded.
+    unsafe {
+        let mut available: ULARGE_INTEGER = std::mem::zeroed();
+        let ok = GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &mut available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        );
+        if ok == 0 {
+            return Err(std::io::Error::last_os_error())
+                .with_context(|| format!("unable to get the free space of '{}'", dir.display()));
+        }
+        Ok(*available.QuadPart())
+    }
+}
+
+/// Check if files could be created in `path`, or in its closest existing ancestor
+/// if it does not exist yet.
+pub fn is_writable(path: &Path) -> bool {
+    existing_ancestor(path).is_some_and(|dir| tempfile::tempfile_in(dir).is_ok())
+}
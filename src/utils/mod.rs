@@ -3,6 +3,7 @@
 //! NOTE: Most of these are moved from the `experimental` branch,
 //! some of them might turns out to be unused, so remember to clean those after version `1.0`.
 
+mod checksum;
 mod download;
 mod extraction;
 mod file_system;
@@ -15,6 +16,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
+pub use checksum::{file_digest, verify_checksum, HashAlgorithm};
 pub use download::{download, download_with_proxy, DownloadOpt};
 pub use extraction::Extractable;
 pub use file_system::*;